@@ -57,7 +57,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     builder.set_sound("default");
     builder.set_badge(1u32);
 
-    let payload = builder.build(device_token.as_ref(), options);
+    let payload = builder.build(device_token.as_str(), options);
     let response = client.send(payload).await?;
 
     println!("Sent: {:?}", response);
@@ -0,0 +1,513 @@
+//! A command line tool sending notifications to APNs, one JSON line of
+//! output per device token. Built with the `cli` feature:
+//!
+//! ```text
+//! cargo run --features cli -- -p key.p8 -k KEY_ID -t TEAM_ID -o com.example.app -d TOKEN -m "Hello"
+//! ```
+
+use a2::client::curl_command;
+use a2::request::payload::Payload;
+use a2::{
+    Client, CollapseId, DeviceToken, Endpoint, Error, Expiration, LocalizedNotificationBuilder, NotificationBuilder,
+    NotificationOptions, PlainNotificationBuilder, Priority, PushType, SilentNotificationBuilder,
+};
+use argparse::{ArgumentParser, Collect, Store, StoreOption, StoreTrue};
+use futures::stream::{self, StreamExt};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::process;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[derive(Default)]
+struct Args {
+    pkcs8: Option<String>,
+    key_id: Option<String>,
+    team_id: Option<String>,
+    certificate: Option<String>,
+    password: String,
+    sandbox: bool,
+    device_tokens: Vec<String>,
+    tokens_file: Option<String>,
+    payload_file: Option<String>,
+    message: Option<String>,
+    title: Option<String>,
+    silent: bool,
+    badge: Option<u32>,
+    sound: Option<String>,
+    category: Option<String>,
+    mutable_content: bool,
+    apns_id: Option<String>,
+    expiration: Option<u64>,
+    priority: Option<String>,
+    topic: Option<String>,
+    collapse_id: Option<String>,
+    push_type: Option<String>,
+    concurrency: usize,
+    dry_run: bool,
+    generate_apns_id: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    pretty_env_logger::init();
+
+    let args = parse_args();
+
+    if let Err(e) = run(args).await {
+        eprintln!("a2: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args() -> Args {
+    let mut args = Args {
+        concurrency: 10,
+        ..Default::default()
+    };
+
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Send notifications to APNs, printing one JSON line per device token");
+        ap.refer(&mut args.pkcs8).add_option(
+            &["-p", "--pkcs8"],
+            StoreOption,
+            "Private key PKCS8 for token authentication",
+        );
+        ap.refer(&mut args.key_id)
+            .add_option(&["-k", "--key_id"], StoreOption, "APNs key ID");
+        ap.refer(&mut args.team_id)
+            .add_option(&["-t", "--team_id"], StoreOption, "APNs team ID");
+        ap.refer(&mut args.certificate).add_option(
+            &["-c", "--certificate"],
+            StoreOption,
+            "Certificate PKCS12 file for certificate authentication",
+        );
+        ap.refer(&mut args.password)
+            .add_option(&["--password"], Store, "Certificate password");
+        ap.refer(&mut args.sandbox)
+            .add_option(&["-s", "--sandbox"], StoreTrue, "Use the development APNs servers");
+        ap.refer(&mut args.device_tokens).add_option(
+            &["-d", "--device_token"],
+            Collect,
+            "APNs device token, can be repeated",
+        );
+        ap.refer(&mut args.tokens_file).add_option(
+            &["-f", "--tokens_file"],
+            StoreOption,
+            "File with one device token per line",
+        );
+        ap.refer(&mut args.payload_file).add_option(
+            &["--payload"],
+            StoreOption,
+            "JSON file with the full payload, instead of the builder options",
+        );
+        ap.refer(&mut args.message)
+            .add_option(&["-m", "--message"], StoreOption, "Notification message");
+        ap.refer(&mut args.title)
+            .add_option(&["--title"], StoreOption, "Notification title, the message is the body");
+        ap.refer(&mut args.silent)
+            .add_option(&["--silent"], StoreTrue, "Send a silent background notification");
+        ap.refer(&mut args.badge)
+            .add_option(&["--badge"], StoreOption, "Badge number");
+        ap.refer(&mut args.sound)
+            .add_option(&["--sound"], StoreOption, "Sound file name");
+        ap.refer(&mut args.category)
+            .add_option(&["--category"], StoreOption, "Notification category");
+        ap.refer(&mut args.mutable_content).add_option(
+            &["--mutable_content"],
+            StoreTrue,
+            "Let the notification service extension modify the content",
+        );
+        ap.refer(&mut args.apns_id)
+            .add_option(&["--apns_id"], StoreOption, "The apns-id header");
+        ap.refer(&mut args.expiration).add_option(
+            &["--expiration"],
+            StoreOption,
+            "The apns-expiration header in UNIX epoch seconds, 0 expires immediately",
+        );
+        ap.refer(&mut args.priority).add_option(
+            &["--priority"],
+            StoreOption,
+            "The apns-priority header: high or normal",
+        );
+        ap.refer(&mut args.topic)
+            .add_option(&["-o", "--topic"], StoreOption, "The apns-topic header");
+        ap.refer(&mut args.collapse_id)
+            .add_option(&["--collapse_id"], StoreOption, "The apns-collapse-id header");
+        ap.refer(&mut args.push_type)
+            .add_option(&["--push_type"], StoreOption, "The apns-push-type header");
+        ap.refer(&mut args.concurrency).add_option(
+            &["--concurrency"],
+            Store,
+            "Notifications in flight at the same time",
+        );
+        ap.refer(&mut args.generate_apns_id).add_option(
+            &["--generate_apns_id"],
+            StoreTrue,
+            "Send a random apns-id with every notification",
+        );
+        ap.refer(&mut args.dry_run).add_option(
+            &["--dry_run"],
+            StoreTrue,
+            "Print the requests as curl commands instead of sending them",
+        );
+        ap.parse_args_or_exit();
+    }
+
+    args
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect(&args)?;
+    client.set_generate_apns_id(args.generate_apns_id);
+
+    let device_tokens = read_device_tokens(&args)?;
+
+    if device_tokens.is_empty() {
+        return Err("no device tokens given, use --device_token or --tokens_file".into());
+    }
+
+    let options = NotificationOptions {
+        apns_id: args.apns_id.as_deref(),
+        apns_expiration: args.expiration.map(|expiration| match expiration {
+            0 => Expiration::Immediately,
+            seconds => Expiration::At(UNIX_EPOCH + Duration::from_secs(seconds)),
+        }),
+        apns_priority: args.priority.as_deref().map(parse_priority).transpose()?,
+        apns_topic: args.topic.as_deref(),
+        apns_collapse_id: args.collapse_id.as_deref().map(CollapseId::new).transpose()?,
+        apns_push_type: args.push_type.as_deref().map(parse_push_type).transpose()?,
+    };
+
+    let raw_payload = match args.payload_file {
+        Some(ref path) => Some(read_payload(path)?),
+        None => None,
+    };
+
+    let payload = match raw_payload {
+        Some(ref data) => raw(data, options),
+        None => build(&args, options)?,
+    };
+
+    if args.dry_run {
+        for device_token in device_tokens {
+            let mut payload = payload.clone();
+
+            // As when sending, a token failing to render fails only its own
+            // line of the output.
+            let rendering = DeviceToken::parse(&device_token).and_then(|parsed| {
+                payload.device_token = parsed;
+                client.render_request(payload)
+            });
+
+            match rendering {
+                Ok(request) => println!("{}", curl_command(&request)),
+                Err(e) => println!("{}", result_line(&device_token, Err(e))),
+            }
+        }
+
+        return Ok(());
+    }
+
+    let mut responses = stream::iter(device_tokens)
+        .map(|device_token| {
+            let mut payload = payload.clone();
+
+            // An invalid token fails only its own line of the output.
+            let sending = DeviceToken::parse(&device_token).map(|parsed| {
+                payload.device_token = parsed;
+                client.send(payload)
+            });
+
+            async move {
+                let result = match sending {
+                    Ok(sending) => sending.await,
+                    Err(e) => Err(e),
+                };
+
+                (device_token, result)
+            }
+        })
+        .buffer_unordered(args.concurrency.max(1));
+
+    while let Some((device_token, result)) = responses.next().await {
+        println!("{}", result_line(&device_token, result));
+    }
+
+    Ok(())
+}
+
+fn connect(args: &Args) -> Result<Client, Box<dyn std::error::Error>> {
+    let endpoint = if args.sandbox {
+        Endpoint::Sandbox
+    } else {
+        Endpoint::Production
+    };
+
+    match (&args.pkcs8, &args.key_id, &args.team_id, &args.certificate) {
+        (Some(pkcs8), Some(key_id), Some(team_id), None) => Ok(Client::token(
+            File::open(pkcs8)?,
+            key_id.as_str(),
+            team_id.as_str(),
+            endpoint,
+        )?),
+        (None, None, None, Some(certificate)) => Ok(Client::certificate(
+            &mut File::open(certificate)?,
+            &args.password,
+            endpoint,
+        )?),
+        _ => Err("give either --pkcs8, --key_id and --team_id, or --certificate".into()),
+    }
+}
+
+fn read_device_tokens(args: &Args) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut device_tokens = args.device_tokens.clone();
+
+    if let Some(ref path) = args.tokens_file {
+        let contents = fs::read_to_string(path)?;
+
+        device_tokens.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from),
+        );
+    }
+
+    Ok(device_tokens)
+}
+
+fn read_payload(path: &str) -> Result<Map<String, Value>, Box<dyn std::error::Error>> {
+    match serde_json::from_str(&fs::read_to_string(path)?)? {
+        Value::Object(data) => Ok(data),
+        _ => Err(format!("the payload in {} is not a JSON object", path).into()),
+    }
+}
+
+/// A payload sent as it is in the JSON file.
+fn raw<'a>(data: &'a Map<String, Value>, options: NotificationOptions<'a>) -> Payload<'a> {
+    Payload {
+        options,
+        device_token: DeviceToken::from(""),
+        aps: None,
+        data: data
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+fn build<'a>(args: &'a Args, options: NotificationOptions<'a>) -> Result<Payload<'a>, Box<dyn std::error::Error>> {
+    if args.silent {
+        let mut builder = SilentNotificationBuilder::new();
+
+        if let Some(badge) = args.badge {
+            builder.set_badge(badge);
+        }
+
+        return Ok(builder.build("", options));
+    }
+
+    let message = args.message.as_deref().ok_or("give --message, --silent or --payload")?;
+
+    match args.title {
+        Some(ref title) => {
+            let mut builder = LocalizedNotificationBuilder::new(title, message);
+
+            if let Some(badge) = args.badge {
+                builder.set_badge(badge);
+            }
+            if let Some(ref sound) = args.sound {
+                builder.set_sound(sound);
+            }
+            if let Some(ref category) = args.category {
+                builder.set_category(category);
+            }
+            if args.mutable_content {
+                builder.set_mutable_content();
+            }
+
+            Ok(builder.build("", options))
+        }
+        None => {
+            let mut builder = PlainNotificationBuilder::new(message);
+
+            if let Some(badge) = args.badge {
+                builder.set_badge(badge);
+            }
+            if let Some(ref sound) = args.sound {
+                builder.set_sound(sound);
+            }
+            if let Some(ref category) = args.category {
+                builder.set_category(category);
+            }
+            if args.mutable_content {
+                builder.set_mutable_content();
+            }
+
+            Ok(builder.build("", options))
+        }
+    }
+}
+
+fn parse_priority(priority: &str) -> Result<Priority, String> {
+    match priority {
+        "high" | "10" => Ok(Priority::High),
+        "normal" | "5" => Ok(Priority::Normal),
+        _ => Err(format!("unknown priority {}, use high or normal", priority)),
+    }
+}
+
+fn parse_push_type(push_type: &str) -> Result<PushType, String> {
+    let push_types = [
+        PushType::Alert,
+        PushType::Background,
+        PushType::Location,
+        PushType::Voip,
+        PushType::Complication,
+        PushType::FileProvider,
+        PushType::Mdm,
+        PushType::LiveActivity,
+        PushType::PushToTalk,
+    ];
+
+    push_types
+        .iter()
+        .find(|known| known.to_string() == push_type)
+        .copied()
+        .ok_or_else(|| format!("unknown push type {}", push_type))
+}
+
+fn result_line(device_token: &str, result: Result<a2::Response, Error>) -> Value {
+    match result {
+        Ok(response) => json!({
+            "device_token": device_token,
+            "status": response.code,
+            "apns_id": response.apns_id,
+            "apns_unique_id": response.apns_unique_id,
+        }),
+        Err(Error::ResponseError(response)) => json!({
+            "device_token": device_token,
+            "status": response.code,
+            "apns_id": response.apns_id,
+            "apns_unique_id": response.apns_unique_id,
+            "reason": response.error.as_ref().map(|body| body.reason.as_str()),
+        }),
+        Err(e) => json!({
+            "device_token": device_token,
+            "apns_id": e.apns_id(),
+            "error": e.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Args {
+        Args {
+            concurrency: 10,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_plain_notification() {
+        let args = Args {
+            message: Some(String::from("Hello")),
+            badge: Some(3),
+            sound: Some(String::from("ping")),
+            mutable_content: true,
+            ..args()
+        };
+
+        let payload = build(&args, Default::default()).unwrap();
+
+        assert_eq!(
+            json!({"aps": {"alert": "Hello", "badge": 3, "sound": "ping", "mutable-content": 1}}).to_string(),
+            payload.to_json_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_localized_notification() {
+        let args = Args {
+            message: Some(String::from("What's up?")),
+            title: Some(String::from("Hi there")),
+            category: Some(String::from("chat")),
+            ..args()
+        };
+
+        let payload = build(&args, Default::default()).unwrap();
+
+        assert_eq!(
+            json!({
+                "aps": {
+                    "alert": {"title": "Hi there", "body": "What's up?"},
+                    "category": "chat"
+                }
+            })
+            .to_string(),
+            payload.to_json_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_silent_notification() {
+        let args = Args {
+            silent: true,
+            badge: Some(1),
+            ..args()
+        };
+
+        let payload = build(&args, Default::default()).unwrap();
+
+        assert_eq!(Some(PushType::Background), payload.options.apns_push_type);
+        assert_eq!(
+            json!({"aps": {"badge": 1, "content-available": 1}}).to_string(),
+            payload.to_json_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_without_a_message() {
+        assert!(build(&args(), Default::default()).is_err());
+    }
+
+    #[test]
+    fn test_raw_payload() {
+        let data = match json!({"aps": {"alert": "Hi"}, "chat": 42}) {
+            Value::Object(data) => data,
+            _ => unreachable!(),
+        };
+
+        let payload = raw(&data, Default::default());
+
+        assert_eq!(
+            json!({"aps": {"alert": "Hi"}, "chat": 42}).to_string(),
+            payload.to_json_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert!(matches!(parse_priority("high"), Ok(Priority::High)));
+        assert!(matches!(parse_priority("5"), Ok(Priority::Normal)));
+        assert!(parse_priority("urgent").is_err());
+
+        assert_eq!(Ok(PushType::LiveActivity), parse_push_type("liveactivity"));
+        assert!(parse_push_type("banner").is_err());
+    }
+
+    #[test]
+    fn test_invalid_device_token_line() {
+        let error = DeviceToken::parse("not a token\r").unwrap_err();
+        let line = result_line("not a token\r", Err(error));
+
+        assert_eq!(json!("not a token\r"), line["device_token"]);
+        assert!(line["error"].as_str().unwrap().starts_with("Invalid device token"));
+    }
+}
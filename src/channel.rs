@@ -0,0 +1,106 @@
+//! Broadcast channels for sending Live Activity updates to all the devices
+//! subscribed to a channel. Managed with the channel methods of the
+//! [Client](../client/struct.Client.html).
+
+use std::convert::TryFrom;
+
+/// Whether APNs stores the latest message of the channel for the devices not
+/// reachable at the time of sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum MessageStoragePolicy {
+    /// The messages are only delivered to the devices connected right away.
+    NoMessageStored,
+    /// The latest message is stored and delivered when the device connects.
+    MostRecentMessageStored,
+}
+
+impl From<MessageStoragePolicy> for u8 {
+    fn from(policy: MessageStoragePolicy) -> u8 {
+        match policy {
+            MessageStoragePolicy::NoMessageStored => 0,
+            MessageStoragePolicy::MostRecentMessageStored => 1,
+        }
+    }
+}
+
+impl TryFrom<u8> for MessageStoragePolicy {
+    type Error = String;
+
+    fn try_from(policy: u8) -> Result<Self, Self::Error> {
+        match policy {
+            0 => Ok(MessageStoragePolicy::NoMessageStored),
+            1 => Ok(MessageStoragePolicy::MostRecentMessageStored),
+            _ => Err(format!("Unknown message storage policy {}", policy)),
+        }
+    }
+}
+
+/// The type of the notifications sent to the channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChannelPushType {
+    /// Updates to a Live Activity.
+    LiveActivity,
+}
+
+/// The configuration of a broadcast channel, given when creating the channel.
+///
+/// ```rust
+/// # use a2::channel::{ChannelConfig, MessageStoragePolicy};
+/// # fn main() {
+/// let config = ChannelConfig::new(MessageStoragePolicy::MostRecentMessageStored);
+///
+/// assert_eq!(
+///     "{\"message-storage-policy\":1,\"push-type\":\"LiveActivity\"}",
+///     serde_json::to_string(&config).unwrap()
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ChannelConfig {
+    pub message_storage_policy: MessageStoragePolicy,
+    pub push_type: ChannelPushType,
+}
+
+impl ChannelConfig {
+    /// A configuration for a Live Activity channel.
+    pub fn new(message_storage_policy: MessageStoragePolicy) -> ChannelConfig {
+        ChannelConfig {
+            message_storage_policy,
+            push_type: ChannelPushType::LiveActivity,
+        }
+    }
+}
+
+/// The response body listing the channels of an app.
+#[derive(Deserialize, Debug)]
+pub(crate) struct ChannelList {
+    pub channels: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_config_parsing() {
+        let config: ChannelConfig = serde_json::from_value(json!({
+            "message-storage-policy": 0,
+            "push-type": "LiveActivity"
+        }))
+        .unwrap();
+
+        assert_eq!(ChannelConfig::new(MessageStoragePolicy::NoMessageStored), config);
+    }
+
+    #[test]
+    fn test_unknown_message_storage_policy() {
+        let config = serde_json::from_value::<ChannelConfig>(json!({
+            "message-storage-policy": 2,
+            "push-type": "LiveActivity"
+        }));
+
+        assert!(config.is_err());
+    }
+}
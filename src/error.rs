@@ -1,6 +1,6 @@
 //! Error and result module
 
-use crate::response::Response;
+use crate::response::{ErrorReason, Response};
 use std::io;
 use thiserror::Error;
 
@@ -10,13 +10,23 @@ pub enum Error {
     #[error("Error serializing to JSON: {0}")]
     SerializeError(#[from] serde_json::Error),
 
-    /// A problem connecting to APNs servers.
-    #[error("Error connecting to APNs: {0}")]
-    ConnectionError(#[from] hyper::Error),
+    /// A problem connecting to APNs servers. Holds the `apns-id` of the
+    /// notification, if it was given or generated by the client.
+    #[error("Error connecting to APNs: {source}")]
+    ConnectionError {
+        apns_id: Option<String>,
+        #[source]
+        source: hyper::Error,
+    },
 
-    /// Couldn't generate an APNs token with the given key.
+    /// Couldn't generate an APNs token with the given key, or couldn't read
+    /// the client certificate.
     #[error("Error creating a signature: {0}")]
-    SignerError(#[from] openssl::error::ErrorStack),
+    SignerError(String),
+
+    /// The TLS configuration for the client certificate was not accepted.
+    #[error("Error configuring TLS: {0}")]
+    TlsError(String),
 
     /// APNs couldn't accept the notification. Contains
     /// [Response](response/struct.Response.html) with additional
@@ -28,14 +38,219 @@ pub enum Error {
             .map(|e| e.reason.to_string())
             .unwrap_or_else(|| "Unknown".to_string())
     )]
-    ResponseError(Response),
+    ResponseError(Box<Response>),
+
+    /// The request could not be built, for example for characters not
+    /// allowed in the URI or in a header.
+    #[error("Error building the request: {0}")]
+    RequestError(#[from] http::Error),
 
     /// Invalid option values given in
     /// [NotificationOptions](request/notification/struct.NotificationOptions.html)
     #[error("Invalid options for APNs payload: {0}")]
     InvalidOptions(String),
 
+    /// The payload is larger than APNs accepts for the push type.
+    #[error("Payload of {size} bytes exceeds the limit of {limit} bytes")]
+    PayloadTooLarge { size: usize, limit: usize },
+
+    /// A notification template could not be rendered from the catalog.
+    #[error("Error rendering a notification template: {0}")]
+    TemplateError(String),
+
+    /// The device token is not a valid hexadecimal APNs token.
+    #[error("Invalid device token: {0}")]
+    InvalidDeviceToken(String),
+
     /// Error reading the certificate or private key.
     #[error("Error in reading a certificate file: {0}")]
     ReadError(#[from] io::Error),
 }
+
+impl Error {
+    /// The `apns-id` of the failed notification. From the response of APNs,
+    /// or for connection errors the id sent with the notification, if any.
+    pub fn apns_id(&self) -> Option<&str> {
+        match self {
+            Error::ConnectionError { apns_id, .. } => apns_id.as_deref(),
+            Error::ResponseError(response) => response.apns_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The reason APNs gave for not accepting the notification, if any.
+    pub fn reason(&self) -> Option<&ErrorReason> {
+        match self {
+            Error::ResponseError(response) => response.error.as_ref().map(|body| &body.reason),
+            _ => None,
+        }
+    }
+
+    /// The notification can be sent again later. True for connection
+    /// problems and for responses APNs documents as temporary, also when the
+    /// response had no body to tell the reason.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::ConnectionError { .. } => true,
+            Error::ResponseError(response) => match self.reason() {
+                Some(reason) => reason.is_retryable(),
+                None => matches!(response.code, 429 | 500 | 503),
+            },
+            _ => false,
+        }
+    }
+
+    /// APNs reported the device token as invalid for the topic. See
+    /// [ErrorReason::should_remove_device_token](../response/enum.ErrorReason.html#method.should_remove_device_token).
+    pub fn should_remove_device_token(&self) -> bool {
+        self.reason()
+            .map(ErrorReason::should_remove_device_token)
+            .unwrap_or(false)
+    }
+
+    /// The certificate, the signing key or the provider token is not usable.
+    pub fn is_auth_problem(&self) -> bool {
+        match self {
+            Error::SignerError(_) => true,
+            _ => self.reason().map(ErrorReason::is_auth_problem).unwrap_or(false),
+        }
+    }
+
+    /// The payload or the notification options are not valid and the same
+    /// notification will never be accepted.
+    pub fn is_payload_problem(&self) -> bool {
+        match self {
+            Error::SerializeError(_)
+            | Error::RequestError(_)
+            | Error::InvalidOptions(_)
+            | Error::PayloadTooLarge { .. } => true,
+            _ => self.reason().map(ErrorReason::is_payload_problem).unwrap_or(false),
+        }
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Self {
+        Error::ConnectionError {
+            apns_id: None,
+            source: e,
+        }
+    }
+}
+
+#[cfg(not(feature = "ring"))]
+impl From<openssl::error::ErrorStack> for Error {
+    fn from(e: openssl::error::ErrorStack) -> Self {
+        Error::SignerError(e.to_string())
+    }
+}
+
+#[cfg(feature = "ring")]
+impl From<ring::error::KeyRejected> for Error {
+    fn from(e: ring::error::KeyRejected) -> Self {
+        Error::SignerError(e.to_string())
+    }
+}
+
+#[cfg(feature = "ring")]
+impl From<ring::error::Unspecified> for Error {
+    fn from(e: ring::error::Unspecified) -> Self {
+        Error::SignerError(e.to_string())
+    }
+}
+
+#[cfg(feature = "ring")]
+impl From<p12_keystore::error::Error> for Error {
+    fn from(e: p12_keystore::error::Error) -> Self {
+        Error::SignerError(e.to_string())
+    }
+}
+
+#[cfg(feature = "ring")]
+impl From<rustls::Error> for Error {
+    fn from(e: rustls::Error) -> Self {
+        Error::TlsError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::ErrorBody;
+
+    fn response_error(code: u16, reason: Option<ErrorReason>) -> Error {
+        Error::ResponseError(Box::new(Response {
+            error: reason.map(|reason| ErrorBody {
+                reason,
+                timestamp: None,
+            }),
+            apns_id: None,
+            apns_unique_id: None,
+            code,
+            elapsed: Default::default(),
+            headers: Default::default(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_connection_error_apns_id() {
+        let (sender, body) = hyper::Body::channel();
+        sender.abort();
+
+        let error = Error::ConnectionError {
+            apns_id: Some(String::from("123e4567-e89b-12d3-a456-426655440000")),
+            source: hyper::body::to_bytes(body).await.unwrap_err(),
+        };
+
+        assert_eq!(Some("123e4567-e89b-12d3-a456-426655440000"), error.apns_id());
+        assert!(error.is_retryable());
+        assert_eq!(None, Error::InvalidOptions(String::from("foo")).apns_id());
+    }
+
+    #[test]
+    fn test_response_error_reason() {
+        let error = response_error(410, Some(ErrorReason::Unregistered));
+
+        assert_eq!(Some(&ErrorReason::Unregistered), error.reason());
+        assert_eq!(None, Error::InvalidOptions(String::from("foo")).reason());
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(response_error(429, Some(ErrorReason::TooManyRequests)).is_retryable());
+        assert!(response_error(503, None).is_retryable());
+        assert!(response_error(403, Some(ErrorReason::ExpiredProviderToken)).is_retryable());
+        assert!(response_error(429, Some(ErrorReason::TooManyProviderTokenUpdates)).is_retryable());
+        assert!(!response_error(400, Some(ErrorReason::BadDeviceToken)).is_retryable());
+        assert!(!response_error(400, None).is_retryable());
+        assert!(!Error::InvalidOptions(String::from("foo")).is_retryable());
+    }
+
+    #[test]
+    fn test_device_token_removal() {
+        assert!(response_error(410, Some(ErrorReason::Unregistered)).should_remove_device_token());
+        assert!(response_error(400, Some(ErrorReason::BadDeviceToken)).should_remove_device_token());
+        assert!(response_error(400, Some(ErrorReason::DeviceTokenNotForTopic)).should_remove_device_token());
+        assert!(!response_error(410, None).should_remove_device_token());
+        assert!(!response_error(429, Some(ErrorReason::TooManyRequests)).should_remove_device_token());
+    }
+
+    #[test]
+    fn test_auth_problems() {
+        assert!(response_error(403, Some(ErrorReason::InvalidProviderToken)).is_auth_problem());
+        assert!(!response_error(400, Some(ErrorReason::BadDeviceToken)).is_auth_problem());
+        assert!(!response_error(429, Some(ErrorReason::TooManyProviderTokenUpdates)).is_auth_problem());
+    }
+
+    #[test]
+    fn test_payload_problems() {
+        assert!(response_error(413, Some(ErrorReason::PayloadTooLarge)).is_payload_problem());
+        assert!(Error::InvalidOptions(String::from("foo")).is_payload_problem());
+        assert!(Error::PayloadTooLarge {
+            size: 4097,
+            limit: 4096
+        }
+        .is_payload_problem());
+        assert!(!response_error(410, Some(ErrorReason::Unregistered)).is_payload_problem());
+    }
+}
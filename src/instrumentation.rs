@@ -0,0 +1,592 @@
+//! Metrics and tracing of the client. Metrics are recorded with the
+//! [metrics](https://docs.rs/metrics) facade when the `metrics` feature is
+//! enabled, and every send gets a [tracing](https://docs.rs/tracing) span when
+//! the `tracing` feature is enabled. Without the features all of these are
+//! no-ops.
+
+#![cfg_attr(not(feature = "metrics"), allow(dead_code))]
+
+use crate::error::Error;
+#[cfg(feature = "metrics")]
+use crate::metrics::{
+    CONNECTIONS, CONNECTION_ERRORS, GOAWAYS, INVALID_REQUESTS, JWT_RENEWALS, REQUESTS_IN_FLIGHT, RESPONSES,
+    RESPONSE_TIME,
+};
+use crate::request::broadcast::BroadcastPayload;
+use crate::request::payload::Payload;
+use crate::response::Response;
+use futures::future::{InspectOk, TryFutureExt};
+use hyper::service::Service;
+use hyper::Uri;
+use std::future::Future;
+use std::task::{Context, Poll};
+
+/// Keeps the request counted as in flight until dropped, also when the
+/// future is dropped before APNs responds.
+pub struct InFlight {
+    _private: (),
+}
+
+impl InFlight {
+    pub fn start() -> InFlight {
+        #[cfg(feature = "metrics")]
+        metrics::gauge!(REQUESTS_IN_FLIGHT).increment(1.0);
+
+        InFlight { _private: () }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        metrics::gauge!(REQUESTS_IN_FLIGHT).decrement(1.0);
+    }
+}
+
+/// The kind of request to APNs, the `operation` label of the metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Send,
+    Broadcast,
+    CreateChannel,
+    ReadChannel,
+    ListChannels,
+    DeleteChannel,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Send => "send",
+            Operation::Broadcast => "broadcast",
+            Operation::CreateChannel => "create_channel",
+            Operation::ReadChannel => "read_channel",
+            Operation::ListChannels => "list_channels",
+            Operation::DeleteChannel => "delete_channel",
+        }
+    }
+}
+
+/// The span of a single send, holding the notification headers and the
+/// outcome of the request.
+pub struct SendSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl SendSpan {
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn new(payload: &Payload<'_>) -> SendSpan {
+        SendSpan {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "apns_send",
+                device_token = %payload.device_token,
+                topic = payload.options.apns_topic,
+                priority = payload.options.apns_priority.as_ref().map(tracing::field::display),
+                push_type = payload.options.apns_push_type.map(tracing::field::display),
+                apns_id = payload.options.apns_id,
+                status = tracing::field::Empty,
+                reason = tracing::field::Empty,
+                outcome = tracing::field::Empty,
+                error = tracing::field::Empty,
+            ),
+        }
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn broadcast(payload: &BroadcastPayload<'_>) -> SendSpan {
+        SendSpan {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "apns_broadcast",
+                channel_id = payload.channel_id,
+                priority = payload.options.apns_priority.as_ref().map(tracing::field::display),
+                apns_id = payload.options.apns_id,
+                status = tracing::field::Empty,
+                reason = tracing::field::Empty,
+                outcome = tracing::field::Empty,
+                error = tracing::field::Empty,
+            ),
+        }
+    }
+
+    /// Runs the future inside of the span.
+    #[cfg(feature = "tracing")]
+    pub fn instrument<F>(self, future: F) -> impl Future<Output = F::Output>
+    where
+        F: Future,
+    {
+        tracing::Instrument::instrument(future, self.span)
+    }
+
+    /// Runs the future inside of the span.
+    #[cfg(not(feature = "tracing"))]
+    pub fn instrument<F>(self, future: F) -> impl Future<Output = F::Output>
+    where
+        F: Future,
+    {
+        future
+    }
+}
+
+/// Records the `apns-id` sent with the request, also when generated by the
+/// client after the span was created.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn request_sent(apns_id: Option<&str>) {
+    #[cfg(feature = "tracing")]
+    {
+        if let Some(apns_id) = apns_id {
+            tracing::Span::current().record("apns_id", apns_id);
+        }
+    }
+}
+
+/// Records a request failed before sending, such as one with a payload too
+/// large or invalid options.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub fn request_invalid(error: &Error, operation: Operation) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+
+        span.record("outcome", "invalid_request");
+        span.record("error", tracing::field::display(error));
+    }
+
+    #[cfg(feature = "metrics")]
+    metrics::counter!(INVALID_REQUESTS, "operation" => operation.as_str(), "error" => error_kind(error)).increment(1);
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub fn response_received(response: &Response, operation: Operation) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+
+        span.record("status", response.code);
+
+        if let Some(ref apns_id) = response.apns_id {
+            span.record("apns_id", apns_id.as_str());
+        }
+
+        match response.error {
+            Some(ref body) => {
+                span.record("outcome", "failure");
+                span.record("reason", body.reason.as_str());
+            }
+            None => {
+                span.record("outcome", "success");
+            }
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    {
+        let status = response.code.to_string();
+        let operation = operation.as_str();
+
+        match response.error {
+            Some(ref body) => {
+                let reason = body.reason.as_str().to_string();
+                metrics::counter!(RESPONSES, "operation" => operation, "status" => status.clone(), "reason" => reason)
+                    .increment(1);
+            }
+            None => {
+                metrics::counter!(RESPONSES, "operation" => operation, "status" => status.clone()).increment(1);
+            }
+        }
+
+        metrics::histogram!(RESPONSE_TIME, "operation" => operation, "status" => status)
+            .record(response.elapsed.as_secs_f64());
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub fn request_failed(error: &hyper::Error, operation: Operation) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+
+        span.record("outcome", "connection_error");
+        span.record("error", tracing::field::display(error));
+    }
+
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(CONNECTION_ERRORS, "operation" => operation.as_str()).increment(1);
+
+        if is_go_away(error) {
+            metrics::counter!(GOAWAYS).increment(1);
+        }
+    }
+}
+
+/// The `error` label of an invalid request.
+#[cfg(feature = "metrics")]
+fn error_kind(error: &Error) -> &'static str {
+    match error {
+        Error::PayloadTooLarge { .. } => "payload_too_large",
+        Error::InvalidOptions(_) => "invalid_options",
+        Error::InvalidDeviceToken(_) => "invalid_device_token",
+        Error::SerializeError(_) => "serialize_error",
+        Error::SignerError(_) => "signer_error",
+        Error::RequestError(_) => "request_error",
+        _ => "other",
+    }
+}
+
+pub fn jwt_renewed() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(JWT_RENEWALS).increment(1);
+}
+
+#[cfg(feature = "metrics")]
+fn is_go_away(error: &hyper::Error) -> bool {
+    let mut source = std::error::Error::source(error);
+
+    while let Some(error) = source {
+        if let Some(h2_error) = error.downcast_ref::<h2::Error>() {
+            return h2_error.is_go_away();
+        }
+
+        source = error.source();
+    }
+
+    false
+}
+
+/// Wraps the TLS connector to record every new connection.
+#[derive(Clone)]
+pub struct InstrumentedConnector<C> {
+    inner: C,
+}
+
+impl<C> InstrumentedConnector<C> {
+    pub fn new(inner: C) -> Self {
+        InstrumentedConnector { inner }
+    }
+}
+
+impl<C> Service<Uri> for InstrumentedConnector<C>
+where
+    C: Service<Uri>,
+{
+    type Response = C::Response;
+    type Error = C::Error;
+    type Future = InspectOk<C::Future, fn(&C::Response)>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        self.inner.call(dst).inspect_ok(connection_established)
+    }
+}
+
+fn connection_established<T>(_: &T) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(CONNECTIONS).increment(1);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod metrics_tests {
+    use super::*;
+    use crate::response::{ErrorBody, ErrorReason};
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use std::time::Duration;
+
+    fn response(code: u16, error: Option<ErrorBody>) -> Response {
+        Response {
+            error,
+            apns_id: None,
+            apns_unique_id: None,
+            code,
+            elapsed: Duration::from_millis(20),
+            headers: Default::default(),
+        }
+    }
+
+    /// Name, labels and value of each metric.
+    type Recorded = Vec<(String, Vec<(String, String)>, DebugValue)>;
+
+    fn recorded<F>(f: F) -> Recorded
+    where
+        F: FnOnce(),
+    {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, f);
+
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let (_, key) = key.into_parts();
+                let labels = key
+                    .labels()
+                    .map(|label| (label.key().to_string(), label.value().to_string()))
+                    .collect();
+
+                (key.name().to_string(), labels, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_response_metrics() {
+        let metrics = recorded(|| {
+            response_received(&response(200, None), Operation::Send);
+            response_received(
+                &response(
+                    410,
+                    Some(ErrorBody {
+                        reason: ErrorReason::Unregistered,
+                        timestamp: None,
+                    }),
+                ),
+                Operation::Send,
+            );
+            response_received(&response(200, None), Operation::ListChannels);
+        });
+
+        let counter = |operation: &str, status: &str, reason: Option<&str>| {
+            let mut labels = vec![
+                (String::from("operation"), String::from(operation)),
+                (String::from("status"), String::from(status)),
+            ];
+
+            if let Some(reason) = reason {
+                labels.push((String::from("reason"), String::from(reason)));
+            }
+
+            metrics
+                .iter()
+                .find(|(name, l, _)| name == RESPONSES && *l == labels)
+                .map(|(_, _, value)| value)
+        };
+
+        assert_eq!(Some(&DebugValue::Counter(1)), counter("send", "200", None));
+        assert_eq!(
+            Some(&DebugValue::Counter(1)),
+            counter("send", "410", Some("Unregistered"))
+        );
+        assert_eq!(Some(&DebugValue::Counter(1)), counter("list_channels", "200", None));
+
+        assert!(metrics.iter().any(|(name, _, _)| name == RESPONSE_TIME));
+    }
+
+    #[test]
+    fn test_invalid_request_metrics() {
+        let metrics = recorded(|| {
+            request_invalid(
+                &Error::PayloadTooLarge {
+                    size: 5000,
+                    limit: 4096,
+                },
+                Operation::Send,
+            );
+        });
+
+        let labels = vec![
+            (String::from("operation"), String::from("send")),
+            (String::from("error"), String::from("payload_too_large")),
+        ];
+
+        assert_eq!(
+            vec![(String::from(INVALID_REQUESTS), labels, DebugValue::Counter(1))],
+            metrics
+        );
+    }
+
+    #[test]
+    fn test_in_flight_metrics() {
+        let metrics = recorded(|| {
+            let _first = InFlight::start();
+            let second = InFlight::start();
+            drop(second);
+        });
+
+        let in_flight = metrics
+            .iter()
+            .find(|(name, _, _)| name == REQUESTS_IN_FLIGHT)
+            .map(|(_, _, value)| value);
+
+        assert_eq!(Some(&DebugValue::Gauge(0.0.into())), in_flight);
+    }
+
+    #[test]
+    fn test_jwt_renewal_metrics() {
+        let metrics = recorded(|| {
+            jwt_renewed();
+            jwt_renewed();
+        });
+
+        assert_eq!(
+            vec![(String::from(JWT_RENEWALS), vec![], DebugValue::Counter(2))],
+            metrics
+        );
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tracing_tests {
+    use super::*;
+    use crate::request::notification::{NotificationBuilder, NotificationOptions, PlainNotificationBuilder};
+    use crate::response::{ErrorBody, ErrorReason};
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+    use tracing_core::span::Current;
+
+    /// Collects the fields of a single span.
+    #[derive(Clone, Default)]
+    struct FieldRecorder {
+        fields: Arc<Mutex<Vec<(String, String)>>>,
+        span: Arc<Mutex<Option<&'static Metadata<'static>>>>,
+        entered: Arc<Mutex<bool>>,
+    }
+
+    impl Visit for FieldRecorder {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields
+                .lock()
+                .unwrap()
+                .push((field.name().to_string(), value.to_string()));
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.fields
+                .lock()
+                .unwrap()
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    impl Subscriber for FieldRecorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            *self.span.lock().unwrap() = Some(span.metadata());
+            span.record(&mut self.clone());
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, values: &Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {
+            *self.entered.lock().unwrap() = true;
+        }
+
+        fn exit(&self, _: &Id) {
+            *self.entered.lock().unwrap() = false;
+        }
+
+        fn current_span(&self) -> Current {
+            match *self.span.lock().unwrap() {
+                Some(metadata) if *self.entered.lock().unwrap() => Current::new(Id::from_u64(1), metadata),
+                _ => Current::none(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_send_span_fields() {
+        let recorder = FieldRecorder::default();
+
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let payload = PlainNotificationBuilder::new("test").build(
+                "0123456789abcdef",
+                NotificationOptions {
+                    apns_topic: Some("a_topic"),
+                    ..Default::default()
+                },
+            );
+
+            let send_span = SendSpan::new(&payload);
+
+            send_span.span.in_scope(|| {
+                response_received(
+                    &Response {
+                        error: Some(ErrorBody {
+                            reason: ErrorReason::Unregistered,
+                            timestamp: None,
+                        }),
+                        apns_id: Some(String::from("an_apns_id")),
+                        apns_unique_id: None,
+                        code: 410,
+                        elapsed: Default::default(),
+                        headers: Default::default(),
+                    },
+                    Operation::Send,
+                )
+            });
+        });
+
+        let fields = recorder.fields.lock().unwrap();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .rev()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        assert_eq!(Some("...abcdef"), field("device_token"));
+        assert_eq!(Some("a_topic"), field("topic"));
+        assert_eq!(Some("an_apns_id"), field("apns_id"));
+        assert_eq!(Some("410"), field("status"));
+        assert_eq!(Some("failure"), field("outcome"));
+        assert_eq!(Some("Unregistered"), field("reason"));
+    }
+
+    #[test]
+    fn test_send_span_of_an_invalid_request() {
+        let recorder = FieldRecorder::default();
+
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let payload = PlainNotificationBuilder::new("test").build("0123456789abcdef", Default::default());
+            let send_span = SendSpan::new(&payload);
+
+            send_span.span.in_scope(|| {
+                request_sent(Some("123e4567-e89b-12d3-a456-426655440000"));
+                request_invalid(
+                    &Error::PayloadTooLarge {
+                        size: 5000,
+                        limit: 4096,
+                    },
+                    Operation::Send,
+                );
+            });
+        });
+
+        let fields = recorder.fields.lock().unwrap();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .rev()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        assert_eq!(Some("123e4567-e89b-12d3-a456-426655440000"), field("apns_id"));
+        assert_eq!(Some("invalid_request"), field("outcome"));
+        assert_eq!(
+            Some("Payload of 5000 bytes exceeds the limit of 4096 bytes"),
+            field("error")
+        );
+    }
+}
@@ -11,6 +11,55 @@
 //! ids. All of these should be available from Xcode or your Apple developer
 //! account.
 //!
+//! ## Backends
+//!
+//! By default the TLS connection, the PKCS12 parsing and the token signing use
+//! the system OpenSSL through the `openssl` feature. To build without OpenSSL,
+//! for example a fully static musl binary, disable the default features and
+//! enable `ring`, which uses [rustls](https://docs.rs/rustls) and
+//! [ring](https://docs.rs/ring) instead:
+//!
+//! ```toml
+//! a2 = { version = "0.6", default-features = false, features = ["ring"] }
+//! ```
+//!
+//! If both features are enabled, for example when adding `ring` without
+//! disabling the default features, rustls and ring are used. The public API,
+//! including the variants of `Error`, is the same with either backend.
+//!
+//! ## Metrics
+//!
+//! With the `metrics` feature the client records the following through the
+//! [metrics](https://docs.rs/metrics) facade, to be exported with any
+//! compatible recorder. The names are also constants in the
+//! [metrics](metrics/index.html) module:
+//!
+//! * `a2_responses_total`, counter of APNs responses labeled with `operation`,
+//!   `status` and `reason`.
+//! * `a2_response_time_seconds`, histogram of the response times labeled with
+//!   `operation` and `status`.
+//! * `a2_requests_in_flight`, gauge of the requests waiting for a response.
+//! * `a2_connection_errors_total`, counter of requests failed without a
+//!   response, labeled with `operation`.
+//! * `a2_invalid_requests_total`, counter of requests failed before sending,
+//!   labeled with `operation` and `error`, such as `payload_too_large` or
+//!   `invalid_options`.
+//! * `a2_goaways_total`, counter of requests failed due to a GOAWAY from APNs.
+//! * `a2_connections_total`, counter of new connections.
+//! * `a2_jwt_renewals_total`, counter of renewed provider tokens.
+//!
+//! The `operation` label is `send`, `broadcast`, `create_channel`,
+//! `read_channel`, `list_channels` or `delete_channel`.
+//!
+//! ## Tracing
+//!
+//! With the `tracing` feature every `send` runs in an `apns_send`
+//! [tracing](https://docs.rs/tracing) span, with the end of the device token,
+//! the topic, the priority and the apns-id as fields, also an apns-id
+//! generated by the client. The span records the `status`, `outcome` and
+//! `reason` of the response, or the `error` if the request failed without a
+//! response or could not be built. The `log` output is not affected.
+//!
 //! ## Payload
 //!
 //! Building the notification payload should be done with the corresponding builders:
@@ -18,6 +67,8 @@
 //! * [PlainNotificationBuilder](request/notification/struct.PlainNotificationBuilder.html) for text only messages.
 //! * [SilentNotificationBuilder](request/notification/struct.SilentNotificationBuilder.html) for silent notifications with custom data.
 //! * [LocalizedNotificationBuilder](request/notification/struct.LocalizedNotificationBuilder.html) for localized rich notifications.
+//! * [GenericNotificationBuilder](request/notification/struct.GenericNotificationBuilder.html) for any `aps` dictionary.
+//! * [LiveActivityNotificationBuilder](request/notification/struct.LiveActivityNotificationBuilder.html) for Live Activity updates, also to broadcast channels.
 //!
 //! The payload generated by the builder [can hold a custom data
 //! section](request/payload/struct.Payload.html#method.add_custom_data),
@@ -25,6 +76,10 @@
 //! [Serde](https://serde.rs/) works, allowing usage of type-safe structs or
 //! dynamic hashmaps to generate the custom data.
 //!
+//! For localizing the texts on the server, a [template
+//! catalog](template/struct.Catalog.html) renders the title, subtitle and
+//! body in the locale of the recipient for the builders.
+//!
 //! ## Client
 //!
 //! The [asynchronous client](client/struct.Client.html), works either with
@@ -119,19 +174,34 @@ extern crate indoc;
 #[macro_use]
 extern crate log;
 
+pub mod channel;
 pub mod client;
 pub mod error;
+mod instrumentation;
+pub mod metrics;
 pub mod request;
 pub mod response;
 mod signer;
+pub mod template;
+mod tls;
+
+#[cfg(not(any(feature = "openssl", feature = "ring")))]
+compile_error!("Either the `openssl` or the `ring` feature must be enabled.");
+
+pub use crate::request::broadcast::BroadcastPayload;
+pub use crate::request::device_token::DeviceToken;
+pub use crate::request::media::MediaAttachment;
 
 pub use crate::request::notification::{
-    CollapseId, LocalizedNotificationBuilder, NotificationBuilder, NotificationOptions, PlainNotificationBuilder,
-    Priority, SilentNotificationBuilder, WebNotificationBuilder, WebPushAlert,
+    CollapseId, ComplicationNotificationBuilder, Expiration, FileProviderNotificationBuilder,
+    GenericNotificationBuilder, LiveActivityEvent, LiveActivityNotificationBuilder, LocalizedNotificationBuilder,
+    LocationNotificationBuilder, MdmNotificationBuilder, NotificationBuilder, NotificationOptions,
+    PlainNotificationBuilder, Priority, PushToTalkNotificationBuilder, PushType, SilentNotificationBuilder,
+    VoipNotificationBuilder, WebNotificationBuilder, WebPushAlert,
 };
 
 pub use crate::response::{ErrorBody, ErrorReason, Response};
 
-pub use crate::client::{Client, Endpoint};
+pub use crate::client::{Client, Endpoint, TokenInvalidationHandler};
 
 pub use crate::error::Error;
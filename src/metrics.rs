@@ -0,0 +1,31 @@
+//! The names of the metrics the client records with the `metrics` feature,
+//! for building dashboards and filtering the exported metrics. See the
+//! [crate documentation](../index.html#metrics) for the labels.
+
+/// Counter of responses from APNs, labeled with `operation`, `status` and,
+/// for failures with a body, `reason`.
+pub const RESPONSES: &str = "a2_responses_total";
+
+/// Histogram of the seconds from sending a request to receiving the full
+/// response, labeled with `operation` and `status`.
+pub const RESPONSE_TIME: &str = "a2_response_time_seconds";
+
+/// Gauge of the requests waiting for a response.
+pub const REQUESTS_IN_FLIGHT: &str = "a2_requests_in_flight";
+
+/// Counter of requests failed without a response from APNs, labeled with
+/// `operation`.
+pub const CONNECTION_ERRORS: &str = "a2_connection_errors_total";
+
+/// Counter of requests failed before sending, e.g. for a payload too large,
+/// labeled with `operation` and `error`.
+pub const INVALID_REQUESTS: &str = "a2_invalid_requests_total";
+
+/// Counter of requests failed due to a GOAWAY frame from APNs.
+pub const GOAWAYS: &str = "a2_goaways_total";
+
+/// Counter of new connections to APNs.
+pub const CONNECTIONS: &str = "a2_connections_total";
+
+/// Counter of renewed provider tokens.
+pub const JWT_RENEWALS: &str = "a2_jwt_renewals_total";
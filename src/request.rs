@@ -1,4 +1,29 @@
 //! The request payload module
+//!
+//! Every `set_` and `add_` method of the notification builders,
+//! [WebPushAlert](notification/struct.WebPushAlert.html) and
+//! [MediaAttachment](media/struct.MediaAttachment.html) has a `with_`
+//! counterpart taking and returning the value instead of a reference, so a
+//! payload can be built in one expression. The `with_` methods of the
+//! fallible setters return a `Result`.
+//!
+//! ```rust
+//! # use a2::request::notification::{NotificationBuilder, PlainNotificationBuilder};
+//! # fn main() {
+//! let payload = PlainNotificationBuilder::new("Hi there")
+//!     .with_badge(1)
+//!     .with_sound("ping")
+//!     .build("token", Default::default());
+//!
+//! assert_eq!(
+//!     "{\"aps\":{\"alert\":\"Hi there\",\"badge\":1,\"sound\":\"ping\"}}",
+//!     &payload.to_json_string().unwrap()
+//! );
+//! # }
+//! ```
 
+pub mod broadcast;
+pub mod device_token;
+pub mod media;
 pub mod notification;
 pub mod payload;
@@ -0,0 +1,58 @@
+//! Payload for a notification sent to a broadcast channel
+
+use crate::channel::MessageStoragePolicy;
+use crate::error::Error;
+use crate::request::notification::NotificationOptions;
+use crate::request::payload::APS;
+use erased_serde::Serialize;
+use serde_json::{self, Value};
+use std::collections::BTreeMap;
+
+/// The data and options for a notification sent to all the devices
+/// subscribed to a broadcast channel. Built with
+/// [LiveActivityNotificationBuilder::build_broadcast](../notification/struct.LiveActivityNotificationBuilder.html#method.build_broadcast)
+/// and sent with
+/// [Client::send_broadcast](../../client/struct.Client.html#method.send_broadcast).
+#[derive(Debug, Clone)]
+pub struct BroadcastPayload<'a> {
+    /// Send options
+    pub options: NotificationOptions<'a>,
+    /// The id of the channel, returned when creating the channel
+    pub channel_id: &'a str,
+    /// The message storage policy the channel was created with. With
+    /// `NoMessageStored`, the notification is sent with an expiration of
+    /// zero, as APNs requires, and any other expiration in the options fails
+    /// with `Error::InvalidOptions`.
+    pub message_storage_policy: MessageStoragePolicy,
+    /// The pre-defined notification payload
+    pub aps: Option<APS<'a>>,
+    /// Application specific payload
+    pub data: BTreeMap<&'a str, Value>,
+}
+
+impl<'a> BroadcastPayload<'a> {
+    /// Sets the message storage policy of the channel.
+    pub fn set_message_storage_policy(&mut self, message_storage_policy: MessageStoragePolicy) -> &mut Self {
+        self.message_storage_policy = message_storage_policy;
+        self
+    }
+
+    /// Client-specific custom data to be added in the payload. See
+    /// [Payload::add_custom_data](../payload/struct.Payload.html#method.add_custom_data).
+    pub fn add_custom_data(&mut self, root_key: &'a str, data: &dyn Serialize) -> Result<&mut Self, Error> {
+        self.data.insert(root_key, serde_json::to_value(data)?);
+
+        Ok(self)
+    }
+
+    /// Combine the APS payload and the custom data to a final payload JSON.
+    /// Returns an error if serialization fails.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_json_string(mut self) -> Result<String, Error> {
+        if let Some(ref aps) = self.aps {
+            self.data.insert("aps", serde_json::to_value(aps)?);
+        }
+
+        Ok(serde_json::to_string(&self.data)?)
+    }
+}
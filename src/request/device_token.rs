@@ -0,0 +1,237 @@
+//! The device token of the receiving device
+
+use crate::error::Error;
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+/// How many characters from the end of the token are shown in `Debug` and
+/// `Display` output.
+const VISIBLE_CHARS: usize = 6;
+
+/// The length of the classic device tokens in bytes.
+const MIN_TOKEN_BYTES: usize = 32;
+
+/// The longest device token APNs accepts in bytes.
+const MAX_TOKEN_BYTES: usize = 100;
+
+/// A token identifying the receiving device.
+///
+/// The token is as secret as an address: it is not shown in full when
+/// formatted with `Debug` or `Display`, only the last few characters are
+/// kept to tell tokens apart in the logs. The full token is available with
+/// [as_str](#method.as_str).
+///
+/// Only the formatting of the token itself is redacted. The request path
+/// `/3/device/<token>` holds the full token, so it shows in the `Debug` output
+/// of a rendered request, in the output of `curl_command` and in the
+/// trace-level logs of hyper and h2.
+///
+/// Converting from a `&str` or a `String` keeps the token as it is. Tokens
+/// from untrusted sources should go through [parse](#method.parse) or
+/// [from_bytes](#method.from_bytes), which check the token is a hexadecimal
+/// string of at least 32 bytes.
+///
+/// ```rust
+/// # use a2::DeviceToken;
+/// # fn main() {
+/// let token = DeviceToken::from("0123456789abcdef0123456789abcdef");
+///
+/// assert_eq!("...abcdef", token.to_string());
+/// assert_eq!("0123456789abcdef0123456789abcdef", token.as_str());
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct DeviceToken<'a> {
+    token: Cow<'a, str>,
+}
+
+impl DeviceToken<'static> {
+    /// Parses a hexadecimal device token. Whitespace is removed and the
+    /// letters are lowercased, so the token can be copied from a log or the
+    /// description of the `Data` the device got from APNs.
+    ///
+    /// ```rust
+    /// # use a2::DeviceToken;
+    /// # fn main() {
+    /// let token = DeviceToken::parse(
+    ///     " 0123456789ABCDEF 0123456789abcdef 0123456789abcdef 0123456789abcdef\n",
+    /// ).unwrap();
+    ///
+    /// assert_eq!(
+    ///     "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+    ///     token.as_str(),
+    /// );
+    /// assert!(DeviceToken::parse("not a token").is_err());
+    /// # }
+    /// ```
+    pub fn parse(token: &str) -> Result<DeviceToken<'static>, Error> {
+        let token: String = token
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+
+        if !token.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::InvalidDeviceToken(String::from(
+                "The device token must be a hexadecimal string",
+            )));
+        }
+
+        if token.len() % 2 == 1 {
+            return Err(Error::InvalidDeviceToken(String::from(
+                "The device token must have an even number of digits",
+            )));
+        }
+
+        Self::check_length(token.len() / 2)?;
+
+        Ok(DeviceToken::from(token))
+    }
+
+    /// Hex encodes the token bytes the device got from APNs.
+    ///
+    /// ```rust
+    /// # use a2::DeviceToken;
+    /// # fn main() {
+    /// let token = DeviceToken::from_bytes(&[0xab; 32]).unwrap();
+    ///
+    /// assert_eq!("ab".repeat(32), token.as_str());
+    /// # }
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<DeviceToken<'static>, Error> {
+        Self::check_length(bytes.len())?;
+
+        let mut token = String::with_capacity(bytes.len() * 2);
+
+        for byte in bytes {
+            write!(token, "{:02x}", byte).unwrap();
+        }
+
+        Ok(DeviceToken::from(token))
+    }
+
+    fn check_length(bytes: usize) -> Result<(), Error> {
+        if (MIN_TOKEN_BYTES..=MAX_TOKEN_BYTES).contains(&bytes) {
+            Ok(())
+        } else {
+            Err(Error::InvalidDeviceToken(format!(
+                "The device token must be {} to {} bytes, got {}",
+                MIN_TOKEN_BYTES, MAX_TOKEN_BYTES, bytes
+            )))
+        }
+    }
+}
+
+impl<'a> DeviceToken<'a> {
+    /// The full token, as sent to APNs.
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+
+    /// The token with only the last few characters visible.
+    pub fn redacted(&self) -> String {
+        match self.token.char_indices().rev().nth(VISIBLE_CHARS - 1) {
+            Some((i, _)) if self.token.len() > 2 * VISIBLE_CHARS => format!("...{}", &self.token[i..]),
+            _ => String::from("..."),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for DeviceToken<'a> {
+    fn from(token: &'a str) -> Self {
+        DeviceToken {
+            token: Cow::Borrowed(token),
+        }
+    }
+}
+
+impl<'a> From<String> for DeviceToken<'a> {
+    fn from(token: String) -> Self {
+        DeviceToken {
+            token: Cow::Owned(token),
+        }
+    }
+}
+
+impl FromStr for DeviceToken<'static> {
+    type Err = Error;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        DeviceToken::parse(token)
+    }
+}
+
+impl<'a, 'b> From<&'b DeviceToken<'a>> for DeviceToken<'a> {
+    fn from(token: &'b DeviceToken<'a>) -> Self {
+        token.clone()
+    }
+}
+
+impl<'a> fmt::Debug for DeviceToken<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("DeviceToken").field(&self.redacted()).finish()
+    }
+}
+
+impl<'a> fmt::Display for DeviceToken<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.redacted())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted_device_token() {
+        assert_eq!("...abcdef", DeviceToken::from("0123456789abcdef").redacted());
+        assert_eq!("...", DeviceToken::from("abcdef").redacted());
+        assert_eq!("...", DeviceToken::from("").redacted());
+    }
+
+    #[test]
+    fn test_device_token_formatting() {
+        let token = DeviceToken::from(String::from("0123456789abcdef"));
+
+        assert_eq!("...abcdef", format!("{}", token));
+        assert_eq!("DeviceToken(\"...abcdef\")", format!("{:?}", token));
+        assert_eq!("0123456789abcdef", token.as_str());
+    }
+
+    #[test]
+    fn test_parse_device_token() {
+        let token = DeviceToken::parse(&"0123456789ABCDEF ".repeat(4)).unwrap();
+
+        assert_eq!("0123456789abcdef".repeat(4), token.as_str());
+    }
+
+    #[test]
+    fn test_parse_long_device_token() {
+        assert!(DeviceToken::parse(&"ab".repeat(80)).is_ok());
+        assert!(DeviceToken::parse(&"ab".repeat(100)).is_ok());
+        assert!(DeviceToken::parse(&"ab".repeat(101)).is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_device_token() {
+        assert!(DeviceToken::parse("").is_err());
+        assert!(DeviceToken::parse(&"ab".repeat(31)).is_err());
+        assert!(DeviceToken::parse(&"xy".repeat(32)).is_err());
+        assert!(DeviceToken::parse(&format!("{}a", "ab".repeat(32))).is_err());
+        assert!("0123-4567".parse::<DeviceToken>().is_err());
+    }
+
+    #[test]
+    fn test_device_token_from_bytes() {
+        let bytes: Vec<u8> = (0..32).collect();
+        let token = DeviceToken::from_bytes(&bytes).unwrap();
+
+        assert_eq!(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            token.as_str()
+        );
+        assert!(DeviceToken::from_bytes(&[0; 16]).is_err());
+    }
+}
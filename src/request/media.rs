@@ -0,0 +1,183 @@
+//! Custom data for rich media notifications
+
+/// A media file for a Notification Service Extension to download and attach
+/// to the notification, following the options of `UNNotificationAttachment`.
+/// Added to a payload with
+/// [Payload::add_media_attachment](../payload/struct.Payload.html#method.add_media_attachment),
+/// which also sets `mutable-content` for the extension to run.
+///
+/// ```rust
+/// # use a2::request::media::MediaAttachment;
+/// # use a2::request::notification::{NotificationBuilder, PlainNotificationBuilder};
+/// # fn main() {
+/// let mut attachment = MediaAttachment::new("https://example.com/cat.jpg");
+/// attachment.set_mime_type("image/jpeg");
+/// attachment.set_thumbnail_hidden(false);
+///
+/// let mut payload = PlainNotificationBuilder::new("A new cat!").build("token", Default::default());
+/// payload.add_media_attachment(&attachment).unwrap();
+///
+/// assert_eq!(
+///     "{\"aps\":{\"alert\":\"A new cat!\",\"mutable-content\":1},\
+///      \"media-attachment\":{\"mimeType\":\"image/jpeg\",\"thumbnailHidden\":false,\"url\":\"https://example.com/cat.jpg\"}}",
+///     &payload.to_json_string().unwrap()
+/// );
+/// # }
+/// ```
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaAttachment<'a> {
+    url: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_hidden: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_clipping_rect: Option<ThumbnailClippingRect>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_time: Option<f64>,
+}
+
+/// The part of an image shown as the thumbnail, in unit coordinates from
+/// zero to one.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct ThumbnailClippingRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl<'a> MediaAttachment<'a> {
+    /// The root key of the attachment in the payload.
+    pub const ROOT_KEY: &'static str = "media-attachment";
+
+    /// An attachment downloaded from the given URL.
+    pub fn new(url: &'a str) -> MediaAttachment<'a> {
+        MediaAttachment {
+            url,
+            mime_type: None,
+            thumbnail_hidden: None,
+            thumbnail_clipping_rect: None,
+            thumbnail_time: None,
+        }
+    }
+
+    /// The type of the file, for the extension to tell the file type when
+    /// the URL has no extension.
+    pub fn set_mime_type(&mut self, mime_type: &'a str) -> &mut Self {
+        self.mime_type = Some(mime_type);
+        self
+    }
+
+    pub fn with_mime_type(mut self, mime_type: &'a str) -> Self {
+        self.set_mime_type(mime_type);
+        self
+    }
+
+    /// Hide the thumbnail of the attachment in the notification.
+    pub fn set_thumbnail_hidden(&mut self, thumbnail_hidden: bool) -> &mut Self {
+        self.thumbnail_hidden = Some(thumbnail_hidden);
+        self
+    }
+
+    pub fn with_thumbnail_hidden(mut self, thumbnail_hidden: bool) -> Self {
+        self.set_thumbnail_hidden(thumbnail_hidden);
+        self
+    }
+
+    /// Show only a part of an image as the thumbnail.
+    pub fn set_thumbnail_clipping_rect(&mut self, rect: ThumbnailClippingRect) -> &mut Self {
+        self.thumbnail_clipping_rect = Some(rect);
+        self
+    }
+
+    pub fn with_thumbnail_clipping_rect(mut self, rect: ThumbnailClippingRect) -> Self {
+        self.set_thumbnail_clipping_rect(rect);
+        self
+    }
+
+    /// The time in seconds of the frame of a video shown as the thumbnail.
+    pub fn set_thumbnail_time(&mut self, seconds: f64) -> &mut Self {
+        self.thumbnail_time = Some(seconds);
+        self
+    }
+
+    pub fn with_thumbnail_time(mut self, seconds: f64) -> Self {
+        self.set_thumbnail_time(seconds);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::notification::{LocalizedNotificationBuilder, NotificationBuilder};
+
+    #[test]
+    fn test_media_attachment_with_thumbnail_options() {
+        let mut attachment = MediaAttachment::new("https://example.com/goal.mp4");
+
+        attachment
+            .set_mime_type("video/mp4")
+            .set_thumbnail_time(4.5)
+            .set_thumbnail_clipping_rect(ThumbnailClippingRect {
+                x: 0.0,
+                y: 0.25,
+                width: 1.0,
+                height: 0.5,
+            });
+
+        let mut payload = LocalizedNotificationBuilder::new("Goal!", "1-0").build("device-token", Default::default());
+        payload.add_media_attachment(&attachment).unwrap();
+
+        let expected_payload = json!({
+            "aps": {
+                "alert": {
+                    "title": "Goal!",
+                    "body": "1-0"
+                },
+                "mutable-content": 1
+            },
+            "media-attachment": {
+                "url": "https://example.com/goal.mp4",
+                "mimeType": "video/mp4",
+                "thumbnailTime": 4.5,
+                "thumbnailClippingRect": {
+                    "x": 0.0,
+                    "y": 0.25,
+                    "width": 1.0,
+                    "height": 0.5
+                }
+            }
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
+
+    #[test]
+    fn test_media_attachment_adds_the_aps() {
+        let mut payload = LocalizedNotificationBuilder::new("Goal!", "1-0").build("device-token", Default::default());
+        payload.aps = None;
+        payload
+            .add_media_attachment(&MediaAttachment::new("https://example.com/goal.jpg"))
+            .unwrap();
+
+        let expected_payload = json!({
+            "aps": {
+                "mutable-content": 1
+            },
+            "media-attachment": {
+                "url": "https://example.com/goal.jpg"
+            }
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
+}
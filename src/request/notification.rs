@@ -1,20 +1,43 @@
 //! The `aps` notification content builders
 
+mod complication;
+mod file_provider;
+mod generic;
+mod live_activity;
 mod localized;
+mod location;
+mod mdm;
 mod options;
 mod plain;
+mod push_to_talk;
 mod silent;
+mod topic;
+mod voip;
 mod web;
 
+pub use self::complication::ComplicationNotificationBuilder;
+pub use self::file_provider::FileProviderNotificationBuilder;
+pub use self::generic::GenericNotificationBuilder;
+pub use self::live_activity::{LiveActivityEvent, LiveActivityNotificationBuilder};
 pub use self::localized::{LocalizedAlert, LocalizedNotificationBuilder};
-pub use self::options::{CollapseId, NotificationOptions, Priority};
+pub use self::location::LocationNotificationBuilder;
+pub use self::mdm::MdmNotificationBuilder;
+pub use self::options::{CollapseId, Expiration, NotificationOptions, Priority, PushType};
 pub use self::plain::PlainNotificationBuilder;
+pub use self::push_to_talk::{ActiveRemoteParticipant, PushToTalkNotificationBuilder};
 pub use self::silent::SilentNotificationBuilder;
+pub use self::voip::VoipNotificationBuilder;
 pub use self::web::{WebNotificationBuilder, WebPushAlert};
 
+use crate::request::device_token::DeviceToken;
 use crate::request::payload::Payload;
 
+/// Builds the payload from the content set with a builder.
 pub trait NotificationBuilder<'a> {
-    /// Generates the request payload to be send with the `Client`.
-    fn build(self, device_token: &'a str, options: NotificationOptions<'a>) -> Payload<'a>;
+    /// Generates the request payload to be send with the `Client`. The
+    /// device token can be a `&str` or a validated
+    /// [DeviceToken](../device_token/struct.DeviceToken.html).
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>;
 }
@@ -0,0 +1,83 @@
+use crate::error::Error;
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::topic::SuffixedTopic;
+use crate::request::notification::{NotificationBuilder, NotificationOptions, PushType};
+use crate::request::payload::Payload;
+use erased_serde::Serialize;
+
+/// A builder to create a notification updating a watchOS complication. The
+/// payload has no alert, only the custom data for the complication.
+///
+/// The builder sets the push type to `complication` and the topic, replacing
+/// the ones in the options.
+///
+/// # Example
+///
+/// ```rust
+/// # use a2::request::notification::{NotificationBuilder, ComplicationNotificationBuilder};
+/// # use std::collections::HashMap;
+/// # fn main() {
+/// let mut score = HashMap::new();
+/// score.insert("home", 2);
+/// score.insert("away", 1);
+///
+/// let mut builder = ComplicationNotificationBuilder::new("com.example.app.complication").unwrap();
+/// builder.add_custom_data("score", &score).unwrap();
+///
+/// let payload = builder.build("device_id", Default::default());
+///
+/// assert_eq!(
+///     "{\"aps\":{},\"score\":{\"away\":1,\"home\":2}}",
+///     &payload.to_json_string().unwrap()
+/// );
+/// # }
+/// ```
+pub struct ComplicationNotificationBuilder<'a> {
+    topic: SuffixedTopic<'a>,
+}
+
+impl<'a> ComplicationNotificationBuilder<'a> {
+    /// Creates a new builder sending to the given topic, which is the bundle
+    /// id of the app with a `.complication` suffix. Fails for any other
+    /// topic.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{ComplicationNotificationBuilder, NotificationBuilder, PushType};
+    /// # fn main() {
+    /// let payload = ComplicationNotificationBuilder::new("com.example.app.complication")
+    ///     .unwrap()
+    ///     .build("token", Default::default());
+    ///
+    /// assert_eq!(Some("com.example.app.complication"), payload.options.apns_topic);
+    /// assert_eq!(Some(PushType::Complication), payload.options.apns_push_type);
+    /// # }
+    /// ```
+    pub fn new(topic: &'a str) -> Result<ComplicationNotificationBuilder<'a>, Error> {
+        Ok(ComplicationNotificationBuilder {
+            topic: SuffixedTopic::new(topic, PushType::Complication)?,
+        })
+    }
+
+    /// Data for the complication. The `root_key` defines the JSON key in the
+    /// root of the payload, and `data` is any value implementing `Serialize`.
+    pub fn add_custom_data(&mut self, root_key: &'a str, data: &dyn Serialize) -> Result<&mut Self, Error> {
+        self.topic.add_custom_data(root_key, data)?;
+
+        Ok(self)
+    }
+
+    pub fn with_custom_data(mut self, root_key: &'a str, data: &dyn Serialize) -> Result<Self, Error> {
+        self.add_custom_data(root_key, data)?;
+
+        Ok(self)
+    }
+}
+
+impl<'a> NotificationBuilder<'a> for ComplicationNotificationBuilder<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
+        self.topic.build(device_token.into(), options)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::Error;
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::topic::SuffixedTopic;
+use crate::request::notification::{NotificationBuilder, NotificationOptions, PushType};
+use crate::request::payload::Payload;
+use serde_json::Value;
+
+/// A builder to create a notification telling a File Provider extension
+/// that the items in a container have changed.
+///
+/// The builder sets the push type to `fileprovider` and the topic, replacing
+/// the ones in the options.
+///
+/// # Example
+///
+/// ```rust
+/// # use a2::request::notification::{NotificationBuilder, FileProviderNotificationBuilder};
+/// # fn main() {
+/// let mut builder = FileProviderNotificationBuilder::new(
+///     "com.example.app.pushkit.fileprovider",
+///     "NSFileProviderWorkingSetContainerItemIdentifier",
+/// )
+/// .unwrap();
+/// builder.set_domain("personal");
+///
+/// let payload = builder.build("device_id", Default::default());
+///
+/// assert_eq!(
+///     "{\"aps\":{},\"container-identifier\":\"NSFileProviderWorkingSetContainerItemIdentifier\",\"domain\":\"personal\"}",
+///     &payload.to_json_string().unwrap()
+/// );
+/// # }
+/// ```
+pub struct FileProviderNotificationBuilder<'a> {
+    topic: SuffixedTopic<'a>,
+    container_identifier: &'a str,
+    domain: Option<&'a str>,
+}
+
+impl<'a> FileProviderNotificationBuilder<'a> {
+    /// Creates a new builder sending to the given topic, which is the bundle
+    /// id of the app with a `.pushkit.fileprovider` suffix, for the items in
+    /// the given container. Fails for any other topic.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{FileProviderNotificationBuilder, NotificationBuilder, PushType};
+    /// # fn main() {
+    /// let payload = FileProviderNotificationBuilder::new(
+    ///     "com.example.app.pushkit.fileprovider",
+    ///     "NSFileProviderRootContainerItemIdentifier",
+    /// )
+    /// .unwrap()
+    /// .build("token", Default::default());
+    ///
+    /// assert_eq!(Some("com.example.app.pushkit.fileprovider"), payload.options.apns_topic);
+    /// assert_eq!(Some(PushType::FileProvider), payload.options.apns_push_type);
+    /// # }
+    /// ```
+    pub fn new(topic: &'a str, container_identifier: &'a str) -> Result<FileProviderNotificationBuilder<'a>, Error> {
+        Ok(FileProviderNotificationBuilder {
+            topic: SuffixedTopic::new(topic, PushType::FileProvider)?,
+            container_identifier,
+            domain: None,
+        })
+    }
+
+    /// The identifier of the File Provider domain having the container.
+    pub fn set_domain(&mut self, domain: &'a str) -> &mut Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    pub fn with_domain(mut self, domain: &'a str) -> Self {
+        self.set_domain(domain);
+        self
+    }
+}
+
+impl<'a> NotificationBuilder<'a> for FileProviderNotificationBuilder<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
+        let mut topic = self.topic;
+        topic.insert("container-identifier", Value::from(self.container_identifier));
+
+        if let Some(domain) = self.domain {
+            topic.insert("domain", Value::from(domain));
+        }
+
+        topic.build(device_token.into(), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_provider_notification() {
+        let payload = FileProviderNotificationBuilder::new(
+            "com.example.app.pushkit.fileprovider",
+            "NSFileProviderRootContainerItemIdentifier",
+        )
+        .unwrap()
+        .build("device-token", Default::default());
+
+        assert_eq!(Some("com.example.app.pushkit.fileprovider"), payload.options.apns_topic);
+        assert_eq!(Some(PushType::FileProvider), payload.options.apns_push_type);
+
+        let expected_payload = json!({
+            "aps": {},
+            "container-identifier": "NSFileProviderRootContainerItemIdentifier"
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
+}
@@ -0,0 +1,149 @@
+use crate::error::Error;
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::{NotificationBuilder, NotificationOptions};
+use crate::request::payload::{Payload, APS};
+use erased_serde::Serialize;
+use serde::ser::Error as _;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// A builder taking the `aps` dictionary as any JSON object, for the keys
+/// and notification types the other builders do not cover. The custom data
+/// and the options are added as with the other builders.
+///
+/// # Example
+///
+/// ```rust
+/// # use a2::request::notification::{GenericNotificationBuilder, NotificationBuilder};
+/// # use serde_json::json;
+/// # fn main() {
+/// let builder = GenericNotificationBuilder::new(&json!({
+///     "alert": {"title": "Hi there", "body": "What's up?"},
+///     "interruption-level": "time-sensitive",
+///     "relevance-score": 0.8
+/// }))
+/// .unwrap();
+///
+/// let mut payload = builder.build("device_id", Default::default());
+/// payload.add_custom_data("chat", &json!({"id": 42})).unwrap();
+///
+/// assert_eq!(
+///     "{\"aps\":{\"alert\":{\"body\":\"What's up?\",\"title\":\"Hi there\"},\
+///      \"interruption-level\":\"time-sensitive\",\"relevance-score\":0.8},\"chat\":{\"id\":42}}",
+///     &payload.to_json_string().unwrap()
+/// );
+/// # }
+/// ```
+pub struct GenericNotificationBuilder {
+    aps: Map<String, Value>,
+}
+
+impl GenericNotificationBuilder {
+    /// Creates a new builder with the `aps` dictionary, any value
+    /// serializing into a JSON object.
+    pub fn new(aps: &dyn Serialize) -> Result<GenericNotificationBuilder, Error> {
+        match serde_json::to_value(aps)? {
+            Value::Object(aps) => Ok(GenericNotificationBuilder { aps }),
+            _ => Err(Error::SerializeError(serde_json::Error::custom(
+                "The aps dictionary must be a JSON object",
+            ))),
+        }
+    }
+
+    /// Sets a key in the `aps` dictionary.
+    pub fn set_aps_key(&mut self, key: &str, value: &dyn Serialize) -> Result<&mut Self, Error> {
+        self.aps.insert(String::from(key), serde_json::to_value(value)?);
+
+        Ok(self)
+    }
+
+    pub fn with_aps_key(mut self, key: &str, value: &dyn Serialize) -> Result<Self, Error> {
+        self.set_aps_key(key, value)?;
+
+        Ok(self)
+    }
+}
+
+impl<'a> NotificationBuilder<'a> for GenericNotificationBuilder {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
+        Payload {
+            aps: Some(APS {
+                extra: self.aps,
+                ..Default::default()
+            }),
+            device_token: device_token.into(),
+            options,
+            data: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::notification::PlainNotificationBuilder;
+
+    #[test]
+    fn test_generic_notification_with_aps_keys() {
+        let mut builder = GenericNotificationBuilder::new(&json!({"content-available": 1})).unwrap();
+        builder.set_aps_key("badge", &3).unwrap();
+
+        let payload = builder
+            .build("device-token", Default::default())
+            .to_json_string()
+            .unwrap();
+
+        let expected_payload = json!({
+            "aps": {
+                "content-available": 1,
+                "badge": 3
+            }
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload);
+    }
+
+    #[test]
+    fn test_generic_notification_with_an_invalid_aps() {
+        assert!(GenericNotificationBuilder::new(&"alert").is_err());
+    }
+
+    #[test]
+    fn test_extra_aps_keys_replace_the_typed_ones() {
+        let mut builder = PlainNotificationBuilder::new("Hi there");
+        builder.set_badge(1);
+
+        let mut payload = builder.build("device-token", Default::default());
+        payload.add_aps_key("badge", &2).unwrap();
+        payload.add_aps_key("interruption-level", &"passive").unwrap();
+
+        let expected_payload = json!({
+            "aps": {
+                "alert": "Hi there",
+                "badge": 2,
+                "interruption-level": "passive"
+            }
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
+
+    #[test]
+    fn test_extra_aps_keys_when_serializing_the_aps() {
+        let mut builder = PlainNotificationBuilder::new("Hi there");
+        builder.set_badge(1).set_sound("ping");
+
+        let mut payload = builder.build("device-token", Default::default());
+        payload.add_aps_key("badge", &2).unwrap();
+
+        assert_eq!(
+            "{\"alert\":\"Hi there\",\"sound\":\"ping\",\"badge\":2}",
+            serde_json::to_string(payload.aps.as_ref().unwrap()).unwrap()
+        );
+    }
+}
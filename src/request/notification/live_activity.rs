@@ -0,0 +1,283 @@
+use crate::channel::MessageStoragePolicy;
+use crate::error::Error;
+use crate::request::broadcast::BroadcastPayload;
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::topic::check_suffix;
+use crate::request::notification::{NotificationBuilder, NotificationOptions, PushType};
+use crate::request::payload::{Payload, APS};
+use erased_serde::Serialize;
+use serde::ser::Error as _;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What the notification does to the Live Activity.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LiveActivityEvent {
+    /// Updates the content state of the activity.
+    Update,
+    /// Ends the activity, keeping the final content state on the Lock Screen
+    /// until the dismissal date.
+    End,
+}
+
+/// A builder to create a notification updating or ending a Live Activity,
+/// sent to the push token of the activity with
+/// [build](#method.build), or to a broadcast channel with
+/// [build_broadcast](#method.build_broadcast).
+///
+/// The content state is any value serializing into a JSON object matching
+/// the `ContentState` of the activity attributes in the app. The dates are
+/// sent in seconds since the epoch, dates before the epoch as zero. The
+/// builder sets the push type to `liveactivity` and the topic, replacing the
+/// ones in the options.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate serde_derive;
+/// # use a2::request::notification::{LiveActivityEvent, LiveActivityNotificationBuilder};
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// # fn main() {
+/// #[derive(Serialize)]
+/// #[serde(rename_all = "camelCase")]
+/// struct Score {
+///     home_score: u32,
+///     away_score: u32,
+/// }
+///
+/// let payload = LiveActivityNotificationBuilder::new(
+///     "com.example.app.push-type.liveactivity",
+///     LiveActivityEvent::Update,
+///     &Score { home_score: 2, away_score: 1 },
+///     UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+/// )
+/// .unwrap()
+/// .with_stale_date(UNIX_EPOCH + Duration::from_secs(1_700_003_600))
+/// .build_broadcast("dHN0LXU=", Default::default());
+///
+/// assert_eq!(
+///     "{\"aps\":{\"content-state\":{\"awayScore\":1,\"homeScore\":2},\"event\":\"update\",\
+///      \"stale-date\":1700003600,\"timestamp\":1700000000}}",
+///     &payload.to_json_string().unwrap()
+/// );
+/// # }
+/// ```
+pub struct LiveActivityNotificationBuilder<'a> {
+    topic: &'a str,
+    event: LiveActivityEvent,
+    content_state: Value,
+    timestamp: SystemTime,
+    dismissal_date: Option<SystemTime>,
+    stale_date: Option<SystemTime>,
+}
+
+impl<'a> LiveActivityNotificationBuilder<'a> {
+    /// Creates a new builder sending to the given topic, which is the bundle
+    /// id of the app with a `.push-type.liveactivity` suffix, with the event,
+    /// the new content state of the activity and the time of the update. The
+    /// device ignores updates older than the one it already shows. Fails for
+    /// any other topic, or if the content state is not a JSON object.
+    pub fn new(
+        topic: &'a str,
+        event: LiveActivityEvent,
+        content_state: &dyn Serialize,
+        timestamp: SystemTime,
+    ) -> Result<LiveActivityNotificationBuilder<'a>, Error> {
+        check_suffix(topic, PushType::LiveActivity)?;
+
+        match serde_json::to_value(content_state)? {
+            content_state @ Value::Object(_) => Ok(LiveActivityNotificationBuilder {
+                topic,
+                event,
+                content_state,
+                timestamp,
+                dismissal_date: None,
+                stale_date: None,
+            }),
+            _ => Err(Error::SerializeError(serde_json::Error::custom(
+                "The content state must be a JSON object",
+            ))),
+        }
+    }
+
+    /// When the system removes an ended activity from the Lock Screen. A
+    /// date in the past removes it right away.
+    pub fn set_dismissal_date(&mut self, dismissal_date: SystemTime) -> &mut Self {
+        self.dismissal_date = Some(dismissal_date);
+        self
+    }
+
+    pub fn with_dismissal_date(mut self, dismissal_date: SystemTime) -> Self {
+        self.set_dismissal_date(dismissal_date);
+        self
+    }
+
+    /// When the content state of the activity becomes outdated.
+    pub fn set_stale_date(&mut self, stale_date: SystemTime) -> &mut Self {
+        self.stale_date = Some(stale_date);
+        self
+    }
+
+    pub fn with_stale_date(mut self, stale_date: SystemTime) -> Self {
+        self.set_stale_date(stale_date);
+        self
+    }
+
+    /// Generates the payload sent to all the devices subscribed to a
+    /// broadcast channel with
+    /// [Client::send_broadcast](../../client/struct.Client.html#method.send_broadcast).
+    /// The message storage policy is `MostRecentMessageStored` until set
+    /// otherwise on the payload.
+    pub fn build_broadcast(self, channel_id: &'a str, options: NotificationOptions<'a>) -> BroadcastPayload<'a> {
+        BroadcastPayload {
+            options: NotificationOptions {
+                apns_topic: Some(self.topic),
+                apns_push_type: Some(PushType::LiveActivity),
+                ..options
+            },
+            channel_id,
+            message_storage_policy: MessageStoragePolicy::MostRecentMessageStored,
+            aps: Some(self.aps()),
+            data: BTreeMap::new(),
+        }
+    }
+
+    fn aps(self) -> APS<'a> {
+        let mut aps = Map::new();
+
+        aps.insert(String::from("event"), json!(self.event));
+        aps.insert(String::from("content-state"), self.content_state);
+        aps.insert(String::from("timestamp"), json!(epoch_seconds(self.timestamp)));
+
+        if let Some(dismissal_date) = self.dismissal_date {
+            aps.insert(String::from("dismissal-date"), json!(epoch_seconds(dismissal_date)));
+        }
+
+        if let Some(stale_date) = self.stale_date {
+            aps.insert(String::from("stale-date"), json!(epoch_seconds(stale_date)));
+        }
+
+        APS {
+            extra: aps,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> NotificationBuilder<'a> for LiveActivityNotificationBuilder<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
+        Payload {
+            options: NotificationOptions {
+                apns_topic: Some(self.topic),
+                apns_push_type: Some(PushType::LiveActivity),
+                ..options
+            },
+            device_token: device_token.into(),
+            aps: Some(self.aps()),
+            data: BTreeMap::new(),
+        }
+    }
+}
+
+fn epoch_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_live_activity_end() {
+        let mut builder = LiveActivityNotificationBuilder::new(
+            "com.example.app.push-type.liveactivity",
+            LiveActivityEvent::End,
+            &json!({"status": "delivered"}),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        )
+        .unwrap();
+
+        builder.set_dismissal_date(UNIX_EPOCH + Duration::from_secs(1_700_001_800));
+
+        let payload = builder.build(
+            "device-token",
+            NotificationOptions {
+                apns_topic: Some("com.example.app"),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            Some("com.example.app.push-type.liveactivity"),
+            payload.options.apns_topic
+        );
+        assert_eq!(Some(PushType::LiveActivity), payload.options.apns_push_type);
+
+        let expected_payload = json!({
+            "aps": {
+                "content-state": {
+                    "status": "delivered"
+                },
+                "dismissal-date": 1_700_001_800,
+                "event": "end",
+                "timestamp": 1_700_000_000
+            }
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
+
+    #[test]
+    fn test_live_activity_broadcast() {
+        let payload = LiveActivityNotificationBuilder::new(
+            "com.example.app.push-type.liveactivity",
+            LiveActivityEvent::Update,
+            &json!({"progress": 0.5}),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        )
+        .unwrap()
+        .build_broadcast("dHN0LXU=", Default::default());
+
+        assert_eq!("dHN0LXU=", payload.channel_id);
+        assert_eq!(
+            Some("com.example.app.push-type.liveactivity"),
+            payload.options.apns_topic
+        );
+        assert_eq!(Some(PushType::LiveActivity), payload.options.apns_push_type);
+        assert_eq!(
+            MessageStoragePolicy::MostRecentMessageStored,
+            payload.message_storage_policy
+        );
+    }
+
+    #[test]
+    fn test_live_activity_with_an_invalid_content_state() {
+        let builder = LiveActivityNotificationBuilder::new(
+            "com.example.app.push-type.liveactivity",
+            LiveActivityEvent::Update,
+            &42,
+            SystemTime::now(),
+        );
+
+        assert!(builder.is_err());
+    }
+
+    #[test]
+    fn test_live_activity_with_an_invalid_topic() {
+        let builder = LiveActivityNotificationBuilder::new(
+            "com.example.app",
+            LiveActivityEvent::Update,
+            &json!({"progress": 0.5}),
+            SystemTime::now(),
+        );
+
+        assert!(matches!(builder, Err(Error::InvalidOptions(_))));
+    }
+}
@@ -1,14 +1,20 @@
+use crate::request::device_token::DeviceToken;
 use crate::request::notification::{NotificationBuilder, NotificationOptions};
 use crate::request::payload::{APSAlert, Payload, APS};
+use serde_json::Map;
 
 use std::{borrow::Cow, collections::BTreeMap};
 
 #[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct LocalizedAlert<'a> {
-    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
     body: &'a str,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subtitle: Option<&'a str>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     title_loc_key: Option<&'a str>,
 
@@ -56,7 +62,7 @@ pub struct LocalizedNotificationBuilder<'a> {
     badge: Option<u32>,
     sound: Option<&'a str>,
     category: Option<&'a str>,
-    mutable_content: u8,
+    mutable_content: bool,
 }
 
 impl<'a> LocalizedNotificationBuilder<'a> {
@@ -69,16 +75,38 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     ///     .build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\"},\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\"}}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
     /// ```
     pub fn new(title: &'a str, body: &'a str) -> LocalizedNotificationBuilder<'a> {
+        let mut builder = Self::without_title(body);
+        builder.alert.title = Some(title);
+        builder
+    }
+
+    /// Creates a new builder with only the body, for an alert without a
+    /// title.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{LocalizedNotificationBuilder, NotificationBuilder};
+    /// # fn main() {
+    /// let payload = LocalizedNotificationBuilder::without_title("a body")
+    ///     .build("token", Default::default());
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\"}}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn without_title(body: &'a str) -> LocalizedNotificationBuilder<'a> {
         LocalizedNotificationBuilder {
             alert: LocalizedAlert {
-                title,
+                title: None,
                 body,
+                subtitle: None,
                 title_loc_key: None,
                 title_loc_args: None,
                 action_loc_key: None,
@@ -89,7 +117,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
             badge: None,
             sound: None,
             category: None,
-            mutable_content: 0,
+            mutable_content: false,
         }
     }
 
@@ -103,7 +131,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\"},\"badge\":4,\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\"},\"badge\":4}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -113,6 +141,11 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_badge(mut self, badge: u32) -> Self {
+        self.set_badge(badge);
+        self
+    }
+
     /// File name of the custom sound to play when receiving the notification.
     ///
     /// ```rust
@@ -123,7 +156,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\"},\"mutable-content\":0,\"sound\":\"ping\"}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\"},\"sound\":\"ping\"}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -133,6 +166,11 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_sound(mut self, sound: &'a str) -> Self {
+        self.set_sound(sound);
+        self
+    }
+
     /// When a notification includes the category key, the system displays the
     /// actions for that category as buttons in the banner or alert interface.
     ///
@@ -144,7 +182,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\"},\"category\":\"cat1\",\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\"},\"category\":\"cat1\"}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -154,6 +192,36 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_category(mut self, category: &'a str) -> Self {
+        self.set_category(category);
+        self
+    }
+
+    /// Additional information shown below the title.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{LocalizedNotificationBuilder, NotificationBuilder};
+    /// # fn main() {
+    /// let mut builder = LocalizedNotificationBuilder::new("a title", "a body");
+    /// builder.set_subtitle("a subtitle");
+    /// let payload = builder.build("token", Default::default());
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"subtitle\":\"a subtitle\",\"title\":\"a title\"}}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn set_subtitle(&mut self, subtitle: &'a str) -> &mut Self {
+        self.alert.subtitle = Some(subtitle);
+        self
+    }
+
+    pub fn with_subtitle(mut self, subtitle: &'a str) -> Self {
+        self.set_subtitle(subtitle);
+        self
+    }
+
     /// The localization key for the notification title.
     ///
     /// ```rust
@@ -164,7 +232,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\",\"title-loc-key\":\"play\"},\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\",\"title-loc-key\":\"play\"}}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -174,6 +242,11 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_title_loc_key(mut self, key: &'a str) -> Self {
+        self.set_title_loc_key(key);
+        self
+    }
+
     /// Arguments for the title localization.
     ///
     /// ```rust
@@ -184,7 +257,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\",\"title-loc-args\":[\"foo\",\"bar\"]},\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"title\":\"a title\",\"title-loc-args\":[\"foo\",\"bar\"]}}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -199,6 +272,14 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_title_loc_args<S>(mut self, args: &'a [S]) -> Self
+    where
+        S: Into<Cow<'a, str>> + AsRef<str>,
+    {
+        self.set_title_loc_args(args);
+        self
+    }
+
     /// The localization key for the action.
     ///
     /// ```rust
@@ -209,7 +290,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"action-loc-key\":\"stop\",\"body\":\"a body\",\"title\":\"a title\"},\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"action-loc-key\":\"stop\",\"body\":\"a body\",\"title\":\"a title\"}}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -219,6 +300,11 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_action_loc_key(mut self, key: &'a str) -> Self {
+        self.set_action_loc_key(key);
+        self
+    }
+
     /// The localization key for the push message body.
     ///
     /// ```rust
@@ -229,7 +315,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"loc-key\":\"lol\",\"title\":\"a title\"},\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"loc-key\":\"lol\",\"title\":\"a title\"}}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -239,6 +325,11 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_loc_key(mut self, key: &'a str) -> Self {
+        self.set_loc_key(key);
+        self
+    }
+
     /// Arguments for the content localization.
     ///
     /// ```rust
@@ -249,7 +340,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"loc-args\":[\"omg\",\"foo\"],\"title\":\"a title\"},\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"loc-args\":[\"omg\",\"foo\"],\"title\":\"a title\"}}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -264,6 +355,14 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_loc_args<S>(mut self, args: &'a [S]) -> Self
+    where
+        S: Into<Cow<'a, str>> + AsRef<str>,
+    {
+        self.set_loc_args(args);
+        self
+    }
+
     /// Image to display in the rich notification.
     ///
     /// ```rust
@@ -274,7 +373,7 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
-    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"launch-image\":\"cat.png\",\"title\":\"a title\"},\"mutable-content\":0}}",
+    ///     "{\"aps\":{\"alert\":{\"body\":\"a body\",\"launch-image\":\"cat.png\",\"title\":\"a title\"}}}",
     ///     &payload.to_json_string().unwrap()
     /// );
     /// # }
@@ -284,6 +383,11 @@ impl<'a> LocalizedNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_launch_image(mut self, image: &'a str) -> Self {
+        self.set_launch_image(image);
+        self
+    }
+
     /// Allow client to modify push content before displaying.
     ///
     /// ```rust
@@ -300,24 +404,34 @@ impl<'a> LocalizedNotificationBuilder<'a> {
     /// # }
     /// ```
     pub fn set_mutable_content(&mut self) -> &mut Self {
-        self.mutable_content = 1;
+        self.mutable_content = true;
+        self
+    }
+
+    pub fn with_mutable_content(mut self) -> Self {
+        self.set_mutable_content();
         self
     }
 }
 
 impl<'a> NotificationBuilder<'a> for LocalizedNotificationBuilder<'a> {
-    fn build(self, device_token: &'a str, options: NotificationOptions<'a>) -> Payload<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
         Payload {
-            aps: APS {
+            aps: Some(APS {
                 alert: Some(APSAlert::Localized(self.alert)),
                 badge: self.badge,
                 sound: self.sound,
                 content_available: None,
                 category: self.category,
-                mutable_content: Some(self.mutable_content),
+                mutable_content: if self.mutable_content { Some(1) } else { None },
+                thread_id: None,
                 url_args: None,
-            },
-            device_token,
+                extra: Map::new(),
+            }),
+            device_token: device_token.into(),
             options,
             data: BTreeMap::new(),
         }
@@ -340,8 +454,7 @@ mod tests {
                 "alert": {
                     "title": "the title",
                     "body": "the body",
-                },
-                "mutable-content": 0
+                }
             }
         })
         .to_string();
@@ -433,8 +546,7 @@ mod tests {
                 "alert": {
                     "title": "the title",
                     "body": "the body",
-                },
-                "mutable-content": 0
+                }
             },
         })
         .to_string();
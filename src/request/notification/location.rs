@@ -0,0 +1,88 @@
+use crate::error::Error;
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::topic::SuffixedTopic;
+use crate::request::notification::{NotificationBuilder, NotificationOptions, Priority, PushType};
+use crate::request::payload::Payload;
+
+/// A builder to create a notification waking up the Location Push Service
+/// Extension of the app to report the location of the device. The payload
+/// has no content.
+///
+/// The builder sets the push type to `location` and the topic, replacing the
+/// ones in the options, and unless given in the options, a high priority.
+///
+/// # Example
+///
+/// ```rust
+/// # use a2::request::notification::{NotificationBuilder, LocationNotificationBuilder};
+/// # fn main() {
+/// let payload = LocationNotificationBuilder::new("com.example.app.location-query")
+///     .unwrap()
+///     .build("device_id", Default::default());
+///
+/// assert_eq!("{\"aps\":{}}", &payload.to_json_string().unwrap());
+/// # }
+/// ```
+pub struct LocationNotificationBuilder<'a> {
+    topic: SuffixedTopic<'a>,
+}
+
+impl<'a> LocationNotificationBuilder<'a> {
+    /// Creates a new builder sending to the given topic, which is the bundle
+    /// id of the app with a `.location-query` suffix. Fails for any other
+    /// topic.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{LocationNotificationBuilder, NotificationBuilder, PushType};
+    /// # fn main() {
+    /// let payload = LocationNotificationBuilder::new("com.example.app.location-query")
+    ///     .unwrap()
+    ///     .build("token", Default::default());
+    ///
+    /// assert_eq!(Some("com.example.app.location-query"), payload.options.apns_topic);
+    /// assert_eq!(Some(PushType::Location), payload.options.apns_push_type);
+    /// # }
+    /// ```
+    pub fn new(topic: &'a str) -> Result<LocationNotificationBuilder<'a>, Error> {
+        Ok(LocationNotificationBuilder {
+            topic: SuffixedTopic::new(topic, PushType::Location)?,
+        })
+    }
+}
+
+impl<'a> NotificationBuilder<'a> for LocationNotificationBuilder<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
+        self.topic.build(
+            device_token.into(),
+            NotificationOptions {
+                apns_priority: options.apns_priority.or(Some(Priority::High)),
+                ..options
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_notification_options() {
+        let payload = LocationNotificationBuilder::new("com.example.app.location-query")
+            .unwrap()
+            .build(
+                "device-token",
+                NotificationOptions {
+                    apns_priority: Some(Priority::Normal),
+                    ..Default::default()
+                },
+            );
+
+        assert_eq!(Some("com.example.app.location-query"), payload.options.apns_topic);
+        assert_eq!(Some(PushType::Location), payload.options.apns_push_type);
+        assert!(matches!(payload.options.apns_priority, Some(Priority::Normal)));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::{NotificationBuilder, NotificationOptions, PushType};
+use crate::request::payload::Payload;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A builder to create an MDM notification, asking an enrolled device to
+/// contact its MDM server. The payload has only the `PushMagic` string the
+/// device sent when enrolling, and no `aps` dictionary.
+///
+/// The builder sets the push type to `mdm`. The notification must be sent
+/// with a client using the MDM push certificate, and the topic is the one of
+/// the certificate.
+///
+/// # Example
+///
+/// ```rust
+/// # use a2::request::notification::{NotificationBuilder, MdmNotificationBuilder, PushType};
+/// # fn main() {
+/// let payload = MdmNotificationBuilder::new("8F1E4A1C-7A0E-4C5B-A6C2-5D1F3C9B2E07")
+///     .build("device_id", Default::default());
+///
+/// assert_eq!(Some(PushType::Mdm), payload.options.apns_push_type);
+/// assert_eq!(
+///     "{\"mdm\":\"8F1E4A1C-7A0E-4C5B-A6C2-5D1F3C9B2E07\"}",
+///     &payload.to_json_string().unwrap()
+/// );
+/// # }
+/// ```
+pub struct MdmNotificationBuilder<'a> {
+    push_magic: &'a str,
+}
+
+impl<'a> MdmNotificationBuilder<'a> {
+    /// Creates a new builder with the `PushMagic` string of the device.
+    pub fn new(push_magic: &'a str) -> MdmNotificationBuilder<'a> {
+        MdmNotificationBuilder { push_magic }
+    }
+}
+
+impl<'a> NotificationBuilder<'a> for MdmNotificationBuilder<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
+        let mut data = BTreeMap::new();
+        data.insert("mdm", Value::from(self.push_magic));
+
+        Payload {
+            aps: None,
+            device_token: device_token.into(),
+            options: NotificationOptions {
+                apns_push_type: Some(PushType::Mdm),
+                ..options
+            },
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mdm_notification() {
+        let payload = MdmNotificationBuilder::new("push-magic").build(
+            "device-token",
+            NotificationOptions {
+                apns_topic: Some("com.apple.mgmt.External.1234"),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(Some("com.apple.mgmt.External.1234"), payload.options.apns_topic);
+        assert_eq!(Some(PushType::Mdm), payload.options.apns_push_type);
+
+        let expected_payload = json!({
+            "mdm": "push-magic"
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
+}
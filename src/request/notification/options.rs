@@ -1,5 +1,10 @@
 use crate::error::Error;
 use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Expirations further ahead are rejected, most likely being milliseconds
+/// instead of seconds. APNs does not store the notifications that long anyway.
+const MAX_EXPIRATION: Duration = Duration::from_secs(365 * 24 * 60 * 60);
 
 #[derive(Debug, Clone)]
 pub struct CollapseId<'a> {
@@ -27,15 +32,16 @@ pub struct NotificationOptions<'a> {
     /// notification to your server.
     pub apns_id: Option<&'a str>,
 
-    /// A UNIX epoch date expressed in seconds (UTC). This header identifies the
-    /// date when the notification is no longer valid and can be discarded.
+    /// The date when the notification is no longer valid and can be
+    /// discarded, sent as UNIX epoch seconds in the `apns-expiration` header.
     ///
-    /// If this value is nonzero, APNs stores the notification and tries to
-    /// deliver it at least once, repeating the attempt as needed if it is unable
-    /// to deliver the notification the first time. If the value is 0, APNs
-    /// treats the notification as if it expires immediately and does not store
-    /// the notification or attempt to redeliver it.
-    pub apns_expiration: Option<u64>,
+    /// If this value is not `Expiration::Immediately`, APNs stores the
+    /// notification and tries to deliver it at least once, repeating the
+    /// attempt as needed if it is unable to deliver the notification the first
+    /// time. Otherwise APNs treats the notification as if it expires
+    /// immediately and does not store the notification or attempt to redeliver
+    /// it.
+    pub apns_expiration: Option<Expiration>,
 
     /// The priority of the notification. If `None`, the APNs server sets the priority to High.
     pub apns_priority: Option<Priority>,
@@ -60,6 +66,10 @@ pub struct NotificationOptions<'a> {
     /// user as a single notification. The value of this key must not exceed 64
     /// bytes.
     pub apns_collapse_id: Option<CollapseId<'a>>,
+
+    /// The type of the notification. Required by watchOS and recommended for
+    /// the other platforms. If `None`, the header is not sent.
+    pub apns_push_type: Option<PushType>,
 }
 
 impl<'a> Default for NotificationOptions<'a> {
@@ -70,10 +80,68 @@ impl<'a> Default for NotificationOptions<'a> {
             apns_priority: None,
             apns_topic: None,
             apns_collapse_id: None,
+            apns_push_type: None,
         }
     }
 }
 
+/// When a notification expires, resolved to a UNIX epoch timestamp when
+/// sending the notification.
+///
+/// ```rust
+/// # use a2::request::notification::Expiration;
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// # fn main() {
+/// let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+///
+/// assert_eq!(0, Expiration::Immediately.resolve(now).unwrap());
+/// assert_eq!(1_600_003_600, Expiration::In(Duration::from_secs(3600)).resolve(now).unwrap());
+///
+/// // Milliseconds by mistake
+/// let expiration = Expiration::At(UNIX_EPOCH + Duration::from_secs(1_600_000_000_000));
+/// assert!(expiration.resolve(now).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiration {
+    /// APNs tries to deliver the notification only once and does not store
+    /// it. Sent as zero.
+    Immediately,
+    /// The notification can be discarded after the given time.
+    At(SystemTime),
+    /// The notification can be discarded after the given time from sending.
+    In(Duration),
+}
+
+impl Expiration {
+    /// The value of the `apns-expiration` header when sending at `now`. Fails
+    /// with `Error::InvalidOptions` if the expiration is more than a year
+    /// ahead, which usually means the time was given in milliseconds, or
+    /// before the epoch.
+    pub fn resolve(&self, now: SystemTime) -> Result<u64, Error> {
+        let too_far_ahead = || {
+            Error::InvalidOptions(String::from(
+                "The apns-expiration is more than a year ahead. Milliseconds instead of seconds?",
+            ))
+        };
+
+        let expires_at = match self {
+            Expiration::Immediately => return Ok(0),
+            Expiration::At(time) => *time,
+            Expiration::In(duration) => now.checked_add(*duration).ok_or_else(too_far_ahead)?,
+        };
+
+        if matches!(expires_at.duration_since(now), Ok(ahead) if ahead > MAX_EXPIRATION) {
+            return Err(too_far_ahead());
+        }
+
+        expires_at
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .map_err(|_| Error::InvalidOptions(String::from("The apns-expiration is before the epoch")))
+    }
+}
+
 /// The importance how fast to bring the notification for the user..
 #[derive(Debug, Clone)]
 pub enum Priority {
@@ -100,6 +168,88 @@ impl fmt::Display for Priority {
     }
 }
 
+/// The value of the `apns-push-type` header, telling APNs and the device
+/// what kind of content the notification has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushType {
+    /// A notification triggering an alert, a sound or a badge.
+    Alert,
+    /// A notification delivering content in the background.
+    Background,
+    /// A notification asking for the location of the device.
+    Location,
+    /// An incoming VoIP call for PushKit.
+    Voip,
+    /// An update to a watchOS complication.
+    Complication,
+    /// A change in a File Provider extension.
+    FileProvider,
+    /// A request for an MDM enrolled device to contact the MDM server.
+    Mdm,
+    /// An update to a Live Activity.
+    LiveActivity,
+    /// An update to a Push to Talk channel.
+    PushToTalk,
+}
+
+impl PushType {
+    /// The largest payload in bytes APNs accepts for this type of a
+    /// notification.
+    pub fn max_payload_size(&self) -> usize {
+        match self {
+            PushType::Voip => 5120,
+            _ => 4096,
+        }
+    }
+
+    /// The suffix of the bundle id in the topic of the push types sent to an
+    /// extension or a framework of the app, such as `.voip`.
+    pub fn topic_suffix(&self) -> Option<&'static str> {
+        match self {
+            PushType::Location => Some(".location-query"),
+            PushType::Voip => Some(".voip"),
+            PushType::Complication => Some(".complication"),
+            PushType::FileProvider => Some(".pushkit.fileprovider"),
+            PushType::PushToTalk => Some(".voip-ptt"),
+            PushType::LiveActivity => Some(".push-type.liveactivity"),
+            _ => None,
+        }
+    }
+
+    /// The push type of a topic ending with one of the known suffixes.
+    pub(crate) fn from_topic_suffix(topic: &str) -> Option<PushType> {
+        [
+            PushType::Location,
+            PushType::Voip,
+            PushType::Complication,
+            PushType::FileProvider,
+            PushType::PushToTalk,
+            PushType::LiveActivity,
+        ]
+        .iter()
+        .copied()
+        .find(|push_type| push_type.topic_suffix().is_some_and(|suffix| topic.ends_with(suffix)))
+    }
+}
+
+impl fmt::Display for PushType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let push_type = match self {
+            PushType::Alert => "alert",
+            PushType::Background => "background",
+            PushType::Location => "location",
+            PushType::Voip => "voip",
+            PushType::Complication => "complication",
+            PushType::FileProvider => "fileprovider",
+            PushType::Mdm => "mdm",
+            PushType::LiveActivity => "liveactivity",
+            PushType::PushToTalk => "pushtotalk",
+        };
+
+        write!(f, "{}", push_type)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,4 +269,44 @@ mod tests {
         let collapse_id = CollapseId::new(str::from_utf8(&long_string).unwrap());
         assert!(collapse_id.is_err());
     }
+
+    #[test]
+    fn test_expiration_at() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let expiration = Expiration::At(UNIX_EPOCH + Duration::from_secs(1_600_086_400));
+
+        assert_eq!(1_600_086_400, expiration.resolve(now).unwrap());
+    }
+
+    #[test]
+    fn test_expiration_in_the_past() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let expiration = Expiration::At(UNIX_EPOCH + Duration::from_secs(1_500_000_000));
+
+        assert_eq!(1_500_000_000, expiration.resolve(now).unwrap());
+    }
+
+    #[test]
+    fn test_expiration_too_far_ahead() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let expiration = Expiration::In(Duration::from_millis(1_600_000_000_000));
+
+        assert!(matches!(expiration.resolve(now), Err(Error::InvalidOptions(_))));
+    }
+
+    #[test]
+    fn test_expiration_overflowing_the_time() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let expiration = Expiration::In(Duration::MAX);
+
+        assert!(matches!(expiration.resolve(now), Err(Error::InvalidOptions(_))));
+    }
+
+    #[test]
+    fn test_expiration_before_the_epoch() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let expiration = Expiration::At(UNIX_EPOCH - Duration::from_secs(1));
+
+        assert!(matches!(expiration.resolve(now), Err(Error::InvalidOptions(_))));
+    }
 }
@@ -1,5 +1,7 @@
+use crate::request::device_token::DeviceToken;
 use crate::request::notification::{NotificationBuilder, NotificationOptions};
 use crate::request::payload::{APSAlert, Payload, APS};
+use serde_json::Map;
 use std::collections::BTreeMap;
 
 /// A builder to create a simple APNs notification payload.
@@ -22,6 +24,7 @@ pub struct PlainNotificationBuilder<'a> {
     badge: Option<u32>,
     sound: Option<&'a str>,
     category: Option<&'a str>,
+    mutable_content: bool,
 }
 
 impl<'a> PlainNotificationBuilder<'a> {
@@ -45,6 +48,7 @@ impl<'a> PlainNotificationBuilder<'a> {
             badge: None,
             sound: None,
             category: None,
+            mutable_content: false,
         }
     }
 
@@ -68,6 +72,11 @@ impl<'a> PlainNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_badge(mut self, badge: u32) -> Self {
+        self.set_badge(badge);
+        self
+    }
+
     /// File name of the custom sound to play when receiving the notification.
     ///
     /// ```rust
@@ -88,6 +97,11 @@ impl<'a> PlainNotificationBuilder<'a> {
         self
     }
 
+    pub fn with_sound(mut self, sound: &'a str) -> Self {
+        self.set_sound(sound);
+        self
+    }
+
     /// When a notification includes the category key, the system displays the
     /// actions for that category as buttons in the banner or alert interface.
     ///
@@ -108,21 +122,58 @@ impl<'a> PlainNotificationBuilder<'a> {
         self.category = Some(category);
         self
     }
+
+    pub fn with_category(mut self, category: &'a str) -> Self {
+        self.set_category(category);
+        self
+    }
+
+    /// Allow client to modify push content before displaying, e.g. in a
+    /// Notification Service Extension downloading a
+    /// [MediaAttachment](../media/struct.MediaAttachment.html).
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{PlainNotificationBuilder, NotificationBuilder};
+    /// # fn main() {
+    /// let mut builder = PlainNotificationBuilder::new("a body");
+    /// builder.set_mutable_content();
+    /// let payload = builder.build("token", Default::default());
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":\"a body\",\"mutable-content\":1}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn set_mutable_content(&mut self) -> &mut Self {
+        self.mutable_content = true;
+        self
+    }
+
+    pub fn with_mutable_content(mut self) -> Self {
+        self.set_mutable_content();
+        self
+    }
 }
 
 impl<'a> NotificationBuilder<'a> for PlainNotificationBuilder<'a> {
-    fn build(self, device_token: &'a str, options: NotificationOptions<'a>) -> Payload<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
         Payload {
-            aps: APS {
+            aps: Some(APS {
                 alert: Some(APSAlert::Plain(self.body)),
                 badge: self.badge,
                 sound: self.sound,
                 content_available: None,
                 category: self.category,
-                mutable_content: None,
+                mutable_content: if self.mutable_content { Some(1) } else { None },
+                thread_id: None,
                 url_args: None,
-            },
-            device_token,
+                extra: Map::new(),
+            }),
+            device_token: device_token.into(),
             options,
             data: BTreeMap::new(),
         }
@@ -220,4 +271,38 @@ mod tests {
 
         assert_eq!(expected_payload, payload_json);
     }
+
+    #[test]
+    fn test_plain_notification_debug_output_hides_the_device_token() {
+        let payload = PlainNotificationBuilder::new("kulli").build("0123456789abcdef", Default::default());
+        let debug = format!("{:?}", payload);
+
+        assert!(!debug.contains("0123456789abcdef"));
+        assert!(debug.contains("...abcdef"));
+    }
+
+    #[test]
+    fn test_plain_notification_built_in_a_chain() {
+        let payload = PlainNotificationBuilder::new("kulli")
+            .with_badge(420)
+            .with_category("cat1")
+            .with_sound("prööt")
+            .with_mutable_content()
+            .build("device-token", Default::default())
+            .to_json_string()
+            .unwrap();
+
+        let expected_payload = json!({
+            "aps": {
+                "alert": "kulli",
+                "badge": 420,
+                "category": "cat1",
+                "mutable-content": 1,
+                "sound": "prööt"
+            }
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload);
+    }
 }
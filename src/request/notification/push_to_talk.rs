@@ -0,0 +1,157 @@
+use crate::error::Error;
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::topic::SuffixedTopic;
+use crate::request::notification::{Expiration, NotificationBuilder, NotificationOptions, Priority, PushType};
+use crate::request::payload::Payload;
+use erased_serde::Serialize;
+
+/// The participant talking on a Push to Talk channel.
+#[derive(Serialize, Debug, Clone)]
+pub struct ActiveRemoteParticipant<'a> {
+    pub name: &'a str,
+}
+
+/// A builder to create a notification for the PushToTalk framework, telling
+/// the app that a remote participant started or stopped talking.
+///
+/// The builder sets the push type to `pushtotalk`, the topic, a high priority
+/// and an expiration of zero, as APNs requires for these notifications,
+/// replacing the ones in the options.
+///
+/// # Example
+///
+/// ```rust
+/// # use a2::request::notification::{NotificationBuilder, PushToTalkNotificationBuilder};
+/// # fn main() {
+/// let mut builder = PushToTalkNotificationBuilder::new("com.example.app.voip-ptt").unwrap();
+/// builder.set_active_remote_participant("Jane");
+///
+/// let payload = builder.build("device_id", Default::default());
+///
+/// assert_eq!(
+///     "{\"activeRemoteParticipant\":{\"name\":\"Jane\"},\"aps\":{}}",
+///     &payload.to_json_string().unwrap()
+/// );
+/// # }
+/// ```
+pub struct PushToTalkNotificationBuilder<'a> {
+    topic: SuffixedTopic<'a>,
+    active_remote_participant: Option<ActiveRemoteParticipant<'a>>,
+}
+
+impl<'a> PushToTalkNotificationBuilder<'a> {
+    /// Creates a new builder sending to the given topic, which is the bundle
+    /// id of the app with a `.voip-ptt` suffix, failing for any other topic.
+    /// Without a participant, the notification tells that nobody is talking.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{PushToTalkNotificationBuilder, NotificationBuilder};
+    /// # fn main() {
+    /// let payload = PushToTalkNotificationBuilder::new("com.example.app.voip-ptt")
+    ///     .unwrap()
+    ///     .build("token", Default::default());
+    ///
+    /// assert_eq!("{\"aps\":{}}", &payload.to_json_string().unwrap());
+    /// # }
+    /// ```
+    pub fn new(topic: &'a str) -> Result<PushToTalkNotificationBuilder<'a>, Error> {
+        Ok(PushToTalkNotificationBuilder {
+            topic: SuffixedTopic::new(topic, PushType::PushToTalk)?,
+            active_remote_participant: None,
+        })
+    }
+
+    /// The name of the participant talking on the channel.
+    pub fn set_active_remote_participant(&mut self, name: &'a str) -> &mut Self {
+        self.active_remote_participant = Some(ActiveRemoteParticipant { name });
+        self
+    }
+
+    pub fn with_active_remote_participant(mut self, name: &'a str) -> Self {
+        self.set_active_remote_participant(name);
+        self
+    }
+
+    /// Data for the app to handle the notification. The `root_key` defines
+    /// the JSON key in the root of the payload, and `data` is any value
+    /// implementing `Serialize`.
+    pub fn add_custom_data(&mut self, root_key: &'a str, data: &dyn Serialize) -> Result<&mut Self, Error> {
+        self.topic.add_custom_data(root_key, data)?;
+
+        Ok(self)
+    }
+
+    pub fn with_custom_data(mut self, root_key: &'a str, data: &dyn Serialize) -> Result<Self, Error> {
+        self.add_custom_data(root_key, data)?;
+
+        Ok(self)
+    }
+}
+
+impl<'a> NotificationBuilder<'a> for PushToTalkNotificationBuilder<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
+        let mut topic = self.topic;
+
+        if let Some(participant) = self.active_remote_participant {
+            topic.insert("activeRemoteParticipant", json!(participant));
+        }
+
+        topic.build(
+            device_token.into(),
+            NotificationOptions {
+                apns_priority: Some(Priority::High),
+                apns_expiration: Some(Expiration::Immediately),
+                ..options
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_push_to_talk_notification_overrides_the_options() {
+        let payload = PushToTalkNotificationBuilder::new("com.example.app.voip-ptt")
+            .unwrap()
+            .build(
+                "device-token",
+                NotificationOptions {
+                    apns_priority: Some(Priority::Normal),
+                    apns_expiration: Some(Expiration::In(Duration::from_secs(3600))),
+                    ..Default::default()
+                },
+            );
+
+        assert_eq!(Some("com.example.app.voip-ptt"), payload.options.apns_topic);
+        assert_eq!(Some(PushType::PushToTalk), payload.options.apns_push_type);
+        assert!(matches!(payload.options.apns_priority, Some(Priority::High)));
+        assert_eq!(Some(Expiration::Immediately), payload.options.apns_expiration);
+    }
+
+    #[test]
+    fn test_push_to_talk_notification_with_participant_and_custom_data() {
+        let mut builder = PushToTalkNotificationBuilder::new("com.example.app.voip-ptt").unwrap();
+
+        builder.set_active_remote_participant("Jane");
+        builder.add_custom_data("channel", &"lobby").unwrap();
+
+        let payload = builder.build("device-token", Default::default());
+
+        let expected_payload = json!({
+            "activeRemoteParticipant": {
+                "name": "Jane"
+            },
+            "aps": {},
+            "channel": "lobby"
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
+}
@@ -1,11 +1,18 @@
-use crate::request::notification::{NotificationBuilder, NotificationOptions};
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::{NotificationBuilder, NotificationOptions, Priority, PushType};
 use crate::request::payload::{Payload, APS};
+use serde_json::Map;
 use std::collections::BTreeMap;
 
 /// A builder to create an APNs silent notification payload which can be used to
 /// send custom data to the user's phone if the user hasn't been running the app
 /// for a while. The custom data needs to be implementing `Serialize` from Serde.
 ///
+/// Unless given in the options, the push type is set to `background` and the
+/// priority to normal, which APNs requires for background updates to be
+/// delivered. Topics with the suffix of another push type, such as `.voip`
+/// or `.complication`, keep the options as given.
+///
 /// # Example
 ///
 /// ```rust
@@ -61,19 +68,31 @@ impl Default for SilentNotificationBuilder {
 }
 
 impl<'a> NotificationBuilder<'a> for SilentNotificationBuilder {
-    fn build(self, device_token: &'a str, options: NotificationOptions<'a>) -> Payload<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
         Payload {
-            aps: APS {
+            aps: Some(APS {
                 alert: None,
                 badge: self.badge,
                 sound: None,
                 content_available: Some(self.content_available),
                 category: None,
                 mutable_content: None,
+                thread_id: None,
                 url_args: None,
+                extra: Map::new(),
+            }),
+            device_token: device_token.into(),
+            options: match options.apns_topic.and_then(PushType::from_topic_suffix) {
+                Some(_) => options,
+                None => NotificationOptions {
+                    apns_push_type: options.apns_push_type.or(Some(PushType::Background)),
+                    apns_priority: options.apns_priority.or(Some(Priority::Normal)),
+                    ..options
+                },
             },
-            device_token,
-            options,
             data: BTreeMap::new(),
         }
     }
@@ -85,6 +104,11 @@ impl SilentNotificationBuilder {
         self.badge = Some(badge);
         self
     }
+
+    pub fn with_badge(mut self, badge: u32) -> Self {
+        self.set_badge(badge);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -109,6 +133,35 @@ mod tests {
         assert_eq!(expected_payload, payload);
     }
 
+    #[test]
+    fn test_silent_notification_options() {
+        let payload = SilentNotificationBuilder::new().build("device-token", Default::default());
+
+        assert_eq!(Some(PushType::Background), payload.options.apns_push_type);
+        assert!(matches!(payload.options.apns_priority, Some(Priority::Normal)));
+
+        let payload = SilentNotificationBuilder::new().build(
+            "device-token",
+            NotificationOptions {
+                apns_priority: Some(Priority::High),
+                ..Default::default()
+            },
+        );
+
+        assert!(matches!(payload.options.apns_priority, Some(Priority::High)));
+
+        let payload = SilentNotificationBuilder::new().build(
+            "device-token",
+            NotificationOptions {
+                apns_topic: Some("com.example.app.voip"),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(None, payload.options.apns_push_type);
+        assert!(payload.options.apns_priority.is_none());
+    }
+
     #[test]
     fn test_silent_notification_with_custom_data() {
         #[derive(Serialize, Debug)]
@@ -0,0 +1,120 @@
+use crate::error::Error;
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::{NotificationOptions, PushType};
+use crate::request::payload::{Payload, APS};
+use erased_serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The topic and the custom data of the builders for the push types sent to
+/// the bundle id of the app with a suffix, such as `.voip`. The payloads
+/// have an empty `aps` dictionary and the content in the custom data.
+pub(crate) struct SuffixedTopic<'a> {
+    topic: &'a str,
+    push_type: PushType,
+    data: BTreeMap<&'a str, Value>,
+}
+
+impl<'a> SuffixedTopic<'a> {
+    /// Fails if the topic does not end with the suffix of the push type.
+    pub fn new(topic: &'a str, push_type: PushType) -> Result<SuffixedTopic<'a>, Error> {
+        check_suffix(topic, push_type)?;
+
+        Ok(SuffixedTopic {
+            topic,
+            push_type,
+            data: BTreeMap::new(),
+        })
+    }
+
+    pub fn add_custom_data(&mut self, root_key: &'a str, data: &dyn Serialize) -> Result<(), Error> {
+        self.insert(root_key, serde_json::to_value(data)?);
+
+        Ok(())
+    }
+
+    pub fn insert(&mut self, root_key: &'a str, value: Value) {
+        self.data.insert(root_key, value);
+    }
+
+    /// The payload with the push type and the checked topic, replacing the
+    /// ones in the options.
+    pub fn build(self, device_token: DeviceToken<'a>, options: NotificationOptions<'a>) -> Payload<'a> {
+        Payload {
+            aps: Some(APS::default()),
+            device_token,
+            options: NotificationOptions {
+                apns_topic: Some(self.topic),
+                apns_push_type: Some(self.push_type),
+                ..options
+            },
+            data: self.data,
+        }
+    }
+}
+
+/// Fails if the topic is not a bundle id with the suffix of the push type.
+pub(crate) fn check_suffix(topic: &str, push_type: PushType) -> Result<(), Error> {
+    let suffix = push_type.topic_suffix().unwrap_or_default();
+
+    if topic.len() <= suffix.len() || !topic.ends_with(suffix) {
+        return Err(Error::InvalidOptions(format!(
+            "The topic of a {} notification must be the bundle id with a {} suffix, got {}",
+            push_type, suffix, topic
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_suffixes() {
+        let topics = [
+            (PushType::Voip, "com.example.app.voip"),
+            (PushType::Complication, "com.example.app.complication"),
+            (PushType::FileProvider, "com.example.app.pushkit.fileprovider"),
+            (PushType::Location, "com.example.app.location-query"),
+            (PushType::PushToTalk, "com.example.app.voip-ptt"),
+            (PushType::LiveActivity, "com.example.app.push-type.liveactivity"),
+        ];
+
+        for (push_type, topic) in topics.iter() {
+            assert!(SuffixedTopic::new(topic, *push_type).is_ok());
+            assert!(SuffixedTopic::new("com.example.app", *push_type).is_err());
+            assert!(SuffixedTopic::new(push_type.topic_suffix().unwrap(), *push_type).is_err());
+        }
+
+        assert!(SuffixedTopic::new("com.example.app.voip", PushType::PushToTalk).is_err());
+        assert!(SuffixedTopic::new("com.example.app.voip-ptt", PushType::Voip).is_err());
+    }
+
+    #[test]
+    fn test_suffixed_topic_payload() {
+        let mut topic = SuffixedTopic::new("com.example.app.complication", PushType::Complication).unwrap();
+        topic.add_custom_data("temperature", &21).unwrap();
+
+        let payload = topic.build(
+            DeviceToken::from("device-token"),
+            NotificationOptions {
+                apns_topic: Some("com.example.app"),
+                apns_push_type: Some(PushType::Background),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(Some("com.example.app.complication"), payload.options.apns_topic);
+        assert_eq!(Some(PushType::Complication), payload.options.apns_push_type);
+
+        let expected_payload = json!({
+            "aps": {},
+            "temperature": 21
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
+}
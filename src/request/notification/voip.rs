@@ -0,0 +1,120 @@
+use crate::error::Error;
+use crate::request::device_token::DeviceToken;
+use crate::request::notification::topic::SuffixedTopic;
+use crate::request::notification::{Expiration, NotificationBuilder, NotificationOptions, Priority, PushType};
+use crate::request::payload::Payload;
+use erased_serde::Serialize;
+
+/// A builder to create a PushKit VoIP notification payload. The payload has
+/// no alert, only the custom data for the app to report the incoming call.
+///
+/// The builder sets the push type and the topic, replacing the ones in the
+/// options, and unless given in the options, a high priority and an
+/// expiration of zero, so the call is not delivered late. VoIP payloads can be up to 5 KB.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate serde_derive;
+/// # use a2::request::notification::{NotificationBuilder, VoipNotificationBuilder};
+/// # fn main() {
+/// #[derive(Serialize)]
+/// struct Call {
+///     caller: &'static str,
+/// }
+///
+/// let mut builder = VoipNotificationBuilder::new("com.example.app.voip").unwrap();
+/// builder.add_custom_data("call", &Call { caller: "Jane" }).unwrap();
+///
+/// let payload = builder.build("device_id", Default::default());
+///
+/// assert_eq!(
+///     "{\"aps\":{},\"call\":{\"caller\":\"Jane\"}}",
+///     &payload.to_json_string().unwrap()
+/// );
+/// # }
+/// ```
+pub struct VoipNotificationBuilder<'a> {
+    topic: SuffixedTopic<'a>,
+}
+
+impl<'a> VoipNotificationBuilder<'a> {
+    /// Creates a new builder sending to the given topic, which is the bundle
+    /// id of the app with a `.voip` suffix. Fails for any other topic.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{Expiration, VoipNotificationBuilder, NotificationBuilder, PushType};
+    /// # fn main() {
+    /// let payload = VoipNotificationBuilder::new("com.example.app.voip")
+    ///     .unwrap()
+    ///     .build("token", Default::default());
+    ///
+    /// assert_eq!(Some("com.example.app.voip"), payload.options.apns_topic);
+    /// assert_eq!(Some(PushType::Voip), payload.options.apns_push_type);
+    /// assert_eq!(Some(Expiration::Immediately), payload.options.apns_expiration);
+    ///
+    /// assert!(VoipNotificationBuilder::new("com.example.app").is_err());
+    /// # }
+    /// ```
+    pub fn new(topic: &'a str) -> Result<VoipNotificationBuilder<'a>, Error> {
+        Ok(VoipNotificationBuilder {
+            topic: SuffixedTopic::new(topic, PushType::Voip)?,
+        })
+    }
+
+    /// Data for the app to handle the call. The `root_key` defines the JSON
+    /// key in the root of the payload, and `data` is any value implementing
+    /// `Serialize`.
+    pub fn add_custom_data(&mut self, root_key: &'a str, data: &dyn Serialize) -> Result<&mut Self, Error> {
+        self.topic.add_custom_data(root_key, data)?;
+
+        Ok(self)
+    }
+
+    pub fn with_custom_data(mut self, root_key: &'a str, data: &dyn Serialize) -> Result<Self, Error> {
+        self.add_custom_data(root_key, data)?;
+
+        Ok(self)
+    }
+}
+
+impl<'a> NotificationBuilder<'a> for VoipNotificationBuilder<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
+        self.topic.build(
+            device_token.into(),
+            NotificationOptions {
+                apns_priority: options.apns_priority.or(Some(Priority::High)),
+                apns_expiration: options.apns_expiration.or(Some(Expiration::Immediately)),
+                ..options
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_voip_notification_options() {
+        let payload = VoipNotificationBuilder::new("com.example.app.voip").unwrap().build(
+            "device-token",
+            NotificationOptions {
+                apns_expiration: Some(Expiration::In(Duration::from_secs(3600))),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(Some("com.example.app.voip"), payload.options.apns_topic);
+        assert_eq!(Some(PushType::Voip), payload.options.apns_push_type);
+        assert!(matches!(payload.options.apns_priority, Some(Priority::High)));
+        assert_eq!(
+            Some(Expiration::In(Duration::from_secs(3600))),
+            payload.options.apns_expiration
+        );
+    }
+}
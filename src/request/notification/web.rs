@@ -1,31 +1,96 @@
+use crate::request::device_token::DeviceToken;
 use crate::request::notification::{NotificationBuilder, NotificationOptions};
 use crate::request::payload::{APSAlert, Payload, APS};
+use serde_json::Map;
 use std::collections::BTreeMap;
 
+/// The alert content of a Safari web push notification.
 #[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct WebPushAlert<'a> {
     pub title: &'a str,
     pub body: &'a str,
-    pub action: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<&'a str>,
 }
 
-/// A builder to create a simple APNs notification payload.
+impl<'a> WebPushAlert<'a> {
+    /// Creates an alert with a title and a body. Without an action Safari
+    /// uses its default label for the button.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{WebNotificationBuilder, NotificationBuilder, WebPushAlert};
+    /// # fn main() {
+    /// let builder = WebNotificationBuilder::new(WebPushAlert::new("Hello", "World"), &["arg1"]);
+    /// let payload = builder.build("token", Default::default());
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":{\"body\":\"World\",\"title\":\"Hello\"},\"url-args\":[\"arg1\"]}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn new(title: &'a str, body: &'a str) -> WebPushAlert<'a> {
+        WebPushAlert {
+            title,
+            body,
+            action: None,
+        }
+    }
+
+    /// The label of the action button.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{WebNotificationBuilder, NotificationBuilder, WebPushAlert};
+    /// # fn main() {
+    /// let mut alert = WebPushAlert::new("Hello", "World");
+    /// alert.set_action("View");
+    /// let payload = WebNotificationBuilder::new(alert, &["arg1"]).build("token", Default::default());
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":{\"action\":\"View\",\"body\":\"World\",\"title\":\"Hello\"},\"url-args\":[\"arg1\"]}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn set_action(&mut self, action: &'a str) -> &mut Self {
+        self.action = Some(action);
+        self
+    }
+
+    pub fn with_action(mut self, action: &'a str) -> Self {
+        self.set_action(action);
+        self
+    }
+}
+
+/// A builder to create a Safari web push notification payload.
 ///
 /// # Example
 ///
 /// ```rust
 /// # use a2::request::notification::{NotificationBuilder, WebNotificationBuilder, WebPushAlert};
+/// # use std::collections::HashMap;
 /// # fn main() {
-/// let mut builder = WebNotificationBuilder::new(WebPushAlert {title: "Hello", body: "World", action: "View"}, &["arg1"]);
+/// let mut builder = WebNotificationBuilder::new(WebPushAlert::new("Hello", "World"), &["arg1"]);
 /// builder.set_sound("prööt");
-/// let payload = builder.build("device_id", Default::default())
-///    .to_json_string().unwrap();
+/// builder.set_badge(1);
+/// builder.set_thread_id("news");
+///
+/// let mut payload = builder.build("device_id", Default::default());
+/// let mut custom_data = HashMap::new();
+/// custom_data.insert("article", "1234");
+/// payload.add_custom_data("custom", &custom_data).unwrap();
+///
+/// let json = payload.to_json_string().unwrap();
 /// # }
 /// ```
 pub struct WebNotificationBuilder<'a> {
     alert: WebPushAlert<'a>,
     sound: Option<&'a str>,
+    badge: Option<u32>,
+    category: Option<&'a str>,
+    thread_id: Option<&'a str>,
     url_args: &'a [&'a str],
 }
 
@@ -35,7 +100,7 @@ impl<'a> WebNotificationBuilder<'a> {
     /// ```rust
     /// # use a2::request::notification::{WebNotificationBuilder, NotificationBuilder, WebPushAlert};
     /// # fn main() {
-    /// let mut builder = WebNotificationBuilder::new(WebPushAlert {title: "Hello", body: "World", action: "View"}, &["arg1"]);
+    /// let mut builder = WebNotificationBuilder::new(WebPushAlert {title: "Hello", body: "World", action: Some("View")}, &["arg1"]);
     /// let payload = builder.build("token", Default::default());
     ///
     /// assert_eq!(
@@ -48,6 +113,9 @@ impl<'a> WebNotificationBuilder<'a> {
         WebNotificationBuilder {
             alert,
             sound: None,
+            badge: None,
+            category: None,
+            thread_id: None,
             url_args: url_args,
         }
     }
@@ -57,7 +125,7 @@ impl<'a> WebNotificationBuilder<'a> {
     /// ```rust
     /// # use a2::request::notification::{WebNotificationBuilder, NotificationBuilder, WebPushAlert};
     /// # fn main() {
-    /// let mut builder = WebNotificationBuilder::new(WebPushAlert {title: "Hello", body: "World", action: "View"}, &["arg1"]);
+    /// let mut builder = WebNotificationBuilder::new(WebPushAlert {title: "Hello", body: "World", action: Some("View")}, &["arg1"]);
     /// builder.set_sound("meow");
     /// let payload = builder.build("token", Default::default());
     ///
@@ -71,21 +139,108 @@ impl<'a> WebNotificationBuilder<'a> {
         self.sound = Some(sound);
         self
     }
+
+    pub fn with_sound(mut self, sound: &'a str) -> Self {
+        self.set_sound(sound);
+        self
+    }
+
+    /// A number to show on a badge on top of the app icon.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{WebNotificationBuilder, NotificationBuilder, WebPushAlert};
+    /// # fn main() {
+    /// let mut builder = WebNotificationBuilder::new(WebPushAlert::new("Hello", "World"), &["arg1"]);
+    /// builder.set_badge(4);
+    /// let payload = builder.build("token", Default::default());
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":{\"body\":\"World\",\"title\":\"Hello\"},\"badge\":4,\"url-args\":[\"arg1\"]}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn set_badge(&mut self, badge: u32) -> &mut Self {
+        self.badge = Some(badge);
+        self
+    }
+
+    pub fn with_badge(mut self, badge: u32) -> Self {
+        self.set_badge(badge);
+        self
+    }
+
+    /// When a notification includes the category key, the system displays the
+    /// actions for that category as buttons in the banner or alert interface.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{WebNotificationBuilder, NotificationBuilder, WebPushAlert};
+    /// # fn main() {
+    /// let mut builder = WebNotificationBuilder::new(WebPushAlert::new("Hello", "World"), &["arg1"]);
+    /// builder.set_category("cat1");
+    /// let payload = builder.build("token", Default::default());
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":{\"body\":\"World\",\"title\":\"Hello\"},\"category\":\"cat1\",\"url-args\":[\"arg1\"]}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn set_category(&mut self, category: &'a str) -> &mut Self {
+        self.category = Some(category);
+        self
+    }
+
+    pub fn with_category(mut self, category: &'a str) -> Self {
+        self.set_category(category);
+        self
+    }
+
+    /// Groups the notification together with the other notifications having
+    /// the same thread id.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{WebNotificationBuilder, NotificationBuilder, WebPushAlert};
+    /// # fn main() {
+    /// let mut builder = WebNotificationBuilder::new(WebPushAlert::new("Hello", "World"), &["arg1"]);
+    /// builder.set_thread_id("news");
+    /// let payload = builder.build("token", Default::default());
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":{\"body\":\"World\",\"title\":\"Hello\"},\"thread-id\":\"news\",\"url-args\":[\"arg1\"]}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn set_thread_id(&mut self, thread_id: &'a str) -> &mut Self {
+        self.thread_id = Some(thread_id);
+        self
+    }
+
+    pub fn with_thread_id(mut self, thread_id: &'a str) -> Self {
+        self.set_thread_id(thread_id);
+        self
+    }
 }
 
 impl<'a> NotificationBuilder<'a> for WebNotificationBuilder<'a> {
-    fn build(self, device_token: &'a str, options: NotificationOptions<'a>) -> Payload<'a> {
+    fn build<T>(self, device_token: T, options: NotificationOptions<'a>) -> Payload<'a>
+    where
+        T: Into<DeviceToken<'a>>,
+    {
         Payload {
-            aps: APS {
+            aps: Some(APS {
                 alert: Some(APSAlert::WebPush(self.alert)),
-                badge: None,
+                badge: self.badge,
                 sound: self.sound,
                 content_available: None,
-                category: None,
+                category: self.category,
                 mutable_content: None,
+                thread_id: self.thread_id,
                 url_args: Some(self.url_args),
-            },
-            device_token,
+                extra: Map::new(),
+            }),
+            device_token: device_token.into(),
             options,
             data: BTreeMap::new(),
         }
@@ -100,7 +255,7 @@ mod tests {
     fn test_webpush_notification() {
         let payload = WebNotificationBuilder::new(
             WebPushAlert {
-                action: "View",
+                action: Some("View"),
                 title: "Hello",
                 body: "world",
             },
@@ -124,4 +279,37 @@ mod tests {
 
         assert_eq!(expected_payload, payload);
     }
+
+    #[test]
+    fn test_webpush_notification_with_full_data() {
+        let mut builder = WebNotificationBuilder::new(WebPushAlert::new("Hello", "world"), &["arg1", "arg2"]);
+
+        builder.set_sound("ping");
+        builder.set_badge(2);
+        builder.set_category("news");
+        builder.set_thread_id("sports");
+
+        let mut payload = builder.build("device-token", Default::default());
+        payload.add_custom_data("custom", &json!({"article": 1234})).unwrap();
+
+        let expected_payload = json!({
+            "aps": {
+                "alert": {
+                    "body": "world",
+                    "title": "Hello"
+                },
+                "badge": 2,
+                "category": "news",
+                "sound": "ping",
+                "thread-id": "sports",
+                "url-args": ["arg1", "arg2"]
+            },
+            "custom": {
+                "article": 1234
+            }
+        })
+        .to_string();
+
+        assert_eq!(expected_payload, payload.to_json_string().unwrap());
+    }
 }
@@ -1,9 +1,12 @@
 //! Payload with `aps` and custom data
 
 use crate::error::Error;
+use crate::request::device_token::DeviceToken;
+use crate::request::media::MediaAttachment;
 use crate::request::notification::{LocalizedAlert, NotificationOptions, WebPushAlert};
 use erased_serde::Serialize;
-use serde_json::{self, Value};
+use serde::ser::SerializeMap;
+use serde_json::{self, Map, Value};
 use std::collections::BTreeMap;
 
 /// The data and options for a push notification.
@@ -12,9 +15,10 @@ pub struct Payload<'a> {
     /// Send options
     pub options: NotificationOptions<'a>,
     /// The token for the receiving device
-    pub device_token: &'a str,
-    /// The pre-defined notification payload
-    pub aps: APS<'a>,
+    pub device_token: DeviceToken<'a>,
+    /// The pre-defined notification payload. Left out of the JSON if `None`,
+    /// for the push types not having one, such as MDM.
+    pub aps: Option<APS<'a>>,
     /// Application specific payload
     pub data: BTreeMap<&'a str, Value>,
 }
@@ -75,51 +79,160 @@ impl<'a> Payload<'a> {
         Ok(self)
     }
 
+    /// Adds a key to the `aps` dictionary, for the keys the builders do not
+    /// support. Creates the dictionary if the payload has none.
+    ///
+    /// ```rust
+    /// # use a2::request::notification::{PlainNotificationBuilder, NotificationBuilder};
+    /// # fn main() {
+    /// let mut payload = PlainNotificationBuilder::new("Hi there").build("token", Default::default());
+    /// payload.add_aps_key("interruption-level", &"time-sensitive").unwrap();
+    ///
+    /// assert_eq!(
+    ///     "{\"aps\":{\"alert\":\"Hi there\",\"interruption-level\":\"time-sensitive\"}}",
+    ///     &payload.to_json_string().unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn add_aps_key(&mut self, key: &str, value: &dyn Serialize) -> Result<&mut Self, Error> {
+        self.aps.get_or_insert_with(APS::default).add_key(key, value)?;
+
+        Ok(self)
+    }
+
+    /// Adds a media attachment for a Notification Service Extension under
+    /// the `media-attachment` root key, and sets `mutable-content` for the
+    /// extension to run, adding the `aps` dictionary if the payload has none.
+    /// See [MediaAttachment](../media/struct.MediaAttachment.html).
+    pub fn add_media_attachment(&mut self, attachment: &MediaAttachment<'_>) -> Result<&mut Self, Error> {
+        self.data
+            .insert(MediaAttachment::ROOT_KEY, serde_json::to_value(attachment)?);
+
+        self.aps.get_or_insert_with(APS::default).mutable_content = Some(1);
+
+        Ok(self)
+    }
+
     /// Combine the APS payload and the custom data to a final payload JSON.
     /// Returns an error if serialization fails.
     #[allow(clippy::wrong_self_convention)]
     pub fn to_json_string(mut self) -> Result<String, Error> {
-        let aps_data = serde_json::to_value(&self.aps)?;
-
-        self.data.insert("aps", aps_data);
+        if let Some(ref aps) = self.aps {
+            self.data.insert("aps", serde_json::to_value(aps)?);
+        }
 
         Ok(serde_json::to_string(&self.data)?)
     }
 }
 
-/// The pre-defined notification data.
-#[derive(Serialize, Debug, Clone)]
-#[serde(rename_all = "kebab-case")]
+/// The pre-defined notification data. Created with `APS::default()`, setting
+/// the fields and adding the keys without a field with
+/// [add_key](#method.add_key).
+///
+/// ```rust
+/// # use a2::request::payload::{APSAlert, APS};
+/// # fn main() {
+/// let mut aps = APS::default();
+/// aps.alert = Some(APSAlert::Plain("Hi there"));
+/// aps.add_key("interruption-level", &"time-sensitive").unwrap();
+///
+/// assert_eq!(
+///     "{\"alert\":\"Hi there\",\"interruption-level\":\"time-sensitive\"}",
+///     &serde_json::to_string(&aps).unwrap()
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
 #[allow(clippy::upper_case_acronyms)]
 pub struct APS<'a> {
     /// The notification content. Can be empty for silent notifications.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub alert: Option<APSAlert<'a>>,
 
     /// A number shown on top of the app icon.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub badge: Option<u32>,
 
     /// The name of the sound file to play when user receives the notification.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub sound: Option<&'a str>,
 
     /// Set to one for silent notifications.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub content_available: Option<u8>,
 
     /// When a notification includes the category key, the system displays the
     /// actions for that category as buttons in the banner or alert interface.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<&'a str>,
 
     /// If set to one, the app can change the notification content before
     /// displaying it to the user.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub mutable_content: Option<u8>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Groups the notification together with the other notifications having
+    /// the same thread id.
+    pub thread_id: Option<&'a str>,
+
     pub url_args: Option<&'a [&'a str]>,
+
+    /// Keys not covered by the fields above, e.g. ones added by Apple after
+    /// this release, set with [add_key](#method.add_key). Replace the typed
+    /// fields with the same key.
+    pub(crate) extra: Map<String, Value>,
+}
+
+/// Serializes the typed fields in kebab-case, leaving out the ones not set and
+/// the ones replaced by an extra key, and then the extra keys.
+impl<'a> serde::Serialize for APS<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+
+        serialize_typed_field(&mut map, &self.extra, "alert", &self.alert)?;
+        serialize_typed_field(&mut map, &self.extra, "badge", &self.badge)?;
+        serialize_typed_field(&mut map, &self.extra, "sound", &self.sound)?;
+        serialize_typed_field(&mut map, &self.extra, "content-available", &self.content_available)?;
+        serialize_typed_field(&mut map, &self.extra, "category", &self.category)?;
+        serialize_typed_field(&mut map, &self.extra, "mutable-content", &self.mutable_content)?;
+        serialize_typed_field(&mut map, &self.extra, "thread-id", &self.thread_id)?;
+        serialize_typed_field(&mut map, &self.extra, "url-args", &self.url_args)?;
+
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+}
+
+fn serialize_typed_field<M, V>(
+    map: &mut M,
+    extra: &Map<String, Value>,
+    key: &str,
+    value: &Option<V>,
+) -> Result<(), M::Error>
+where
+    M: SerializeMap,
+    V: serde::Serialize,
+{
+    match value {
+        Some(value) if !extra.contains_key(key) => map.serialize_entry(key, value),
+        _ => Ok(()),
+    }
+}
+
+impl<'a> APS<'a> {
+    /// Adds a key without a typed field, replacing the typed field if the
+    /// key is the same.
+    pub fn add_key(&mut self, key: &str, value: &dyn Serialize) -> Result<&mut Self, Error> {
+        self.extra.insert(String::from(key), serde_json::to_value(value)?);
+
+        Ok(self)
+    }
+
+    /// True for a background update, which only sets `content-available` and
+    /// does not alert the user.
+    pub(crate) fn is_content_available_only(&self) -> bool {
+        self.content_available.is_some() && self.alert.is_none() && self.sound.is_none() && self.badge.is_none()
+    }
 }
 
 /// Different notification content types.
@@ -1,6 +1,9 @@
 //! The APNs response types
 
+use http::HeaderMap;
+use serde::{Deserialize, Deserializer};
 use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// The response data from APNs.
 #[derive(Debug)]
@@ -12,6 +15,10 @@ pub struct Response {
     /// generated by APNs.
     pub apns_id: Option<String>,
 
+    /// An identifier for looking up the notification in the Push Notification
+    /// Console. Only returned by the sandbox environment.
+    pub apns_unique_id: Option<String>,
+
     /// The HTTP response code.
     ///
     /// * 200 Success
@@ -24,6 +31,12 @@ pub struct Response {
     /// * 500 Internal server error.
     /// * 503 The server is shutting down and unavailable.
     pub code: u16,
+
+    /// The time from sending the request to receiving the full response.
+    pub elapsed: Duration,
+
+    /// All headers of the HTTP response.
+    pub headers: HeaderMap,
 }
 
 /// The response body from APNs. Only available for errors.
@@ -38,11 +51,25 @@ pub struct ErrorBody {
     ///
     /// Stop pushing notifications until the device registers a token with a
     /// later timestamp with your provider.
-    pub timestamp: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    pub timestamp: Option<SystemTime>,
+}
+
+/// APNs gives the timestamp in milliseconds since the UNIX epoch.
+fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<Option<SystemTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let millis = Option::<u64>::deserialize(deserializer)?;
+
+    Ok(millis.map(|millis| UNIX_EPOCH + Duration::from_millis(millis)))
 }
 
 /// A description what went wrong with the push notification.
-#[derive(Deserialize, Debug, PartialEq)]
+///
+/// Reasons added by Apple after this version of the crate are kept as
+/// `Unknown`, holding the reason string from the response.
+#[derive(Debug, PartialEq, Clone)]
 pub enum ErrorReason {
     /// The collapse identifier exceeds the maximum allowed size.
     BadCollapseId,
@@ -63,6 +90,27 @@ pub enum ErrorReason {
     /// The `apns_topic` in `NotificationOptions` is bad.
     BadTopic,
 
+    /// The `apns-push-type` value is invalid.
+    InvalidPushType,
+
+    /// The `apns-channel-id` of the broadcast channel is bad.
+    BadChannelId,
+
+    /// The broadcast channel does not exist.
+    ChannelNotRegistered,
+
+    /// The `apns-channel-id` header was required but not specified.
+    MissingChannelId,
+
+    /// The app has reached the maximum number of broadcast channels.
+    CannotCreateChannelConfig,
+
+    /// Broadcast push notifications are not enabled for the app.
+    FeatureNotEnabled,
+
+    /// The `apns-push-type` header was required but not specified.
+    MissingPushType,
+
     /// The device token does not match the specified topic.
     DeviceTokenNotForTopic,
 
@@ -106,12 +154,23 @@ pub enum ErrorReason {
     /// header was missing or no provider token was specified.
     MissingProviderToken,
 
+    /// The key id in the provider token isn't related to the key id of the
+    /// token used in the first push of this connection.
+    UnrelatedKeyIdInToken,
+
+    /// The environment of the key in the provider token doesn't match the
+    /// environment of the endpoint.
+    BadEnvironmentKeyInToken,
+
     /// The request path value is bad.
     BadPath,
 
     /// The request method was not `POST`.
     MethodNotAllowed,
 
+    /// The device token has expired.
+    ExpiredToken,
+
     /// The device token is inactive for the specified topic. You should stop sending
     /// notifications to this token.
     Unregistered,
@@ -133,11 +192,180 @@ pub enum ErrorReason {
 
     /// The server is shutting down.
     Shutdown,
+
+    /// A reason not known by this version of the crate.
+    Unknown(String),
+}
+
+impl ErrorReason {
+    /// The reason as written in the APNs response.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ErrorReason::BadCollapseId => "BadCollapseId",
+            ErrorReason::BadDeviceToken => "BadDeviceToken",
+            ErrorReason::BadExpirationDate => "BadExpirationDate",
+            ErrorReason::BadMessageId => "BadMessageId",
+            ErrorReason::BadPriority => "BadPriority",
+            ErrorReason::BadTopic => "BadTopic",
+            ErrorReason::InvalidPushType => "InvalidPushType",
+            ErrorReason::BadChannelId => "BadChannelId",
+            ErrorReason::ChannelNotRegistered => "ChannelNotRegistered",
+            ErrorReason::MissingChannelId => "MissingChannelId",
+            ErrorReason::CannotCreateChannelConfig => "CannotCreateChannelConfig",
+            ErrorReason::FeatureNotEnabled => "FeatureNotEnabled",
+            ErrorReason::MissingPushType => "MissingPushType",
+            ErrorReason::DeviceTokenNotForTopic => "DeviceTokenNotForTopic",
+            ErrorReason::DuplicateHeaders => "DuplicateHeaders",
+            ErrorReason::IdleTimeout => "IdleTimeout",
+            ErrorReason::MissingDeviceToken => "MissingDeviceToken",
+            ErrorReason::MissingTopic => "MissingTopic",
+            ErrorReason::PayloadEmpty => "PayloadEmpty",
+            ErrorReason::TopicDisallowed => "TopicDisallowed",
+            ErrorReason::BadCertificate => "BadCertificate",
+            ErrorReason::BadCertificateEnvironment => "BadCertificateEnvironment",
+            ErrorReason::ExpiredProviderToken => "ExpiredProviderToken",
+            ErrorReason::Forbidden => "Forbidden",
+            ErrorReason::InvalidProviderToken => "InvalidProviderToken",
+            ErrorReason::MissingProviderToken => "MissingProviderToken",
+            ErrorReason::UnrelatedKeyIdInToken => "UnrelatedKeyIdInToken",
+            ErrorReason::BadEnvironmentKeyInToken => "BadEnvironmentKeyInToken",
+            ErrorReason::BadPath => "BadPath",
+            ErrorReason::MethodNotAllowed => "MethodNotAllowed",
+            ErrorReason::ExpiredToken => "ExpiredToken",
+            ErrorReason::Unregistered => "Unregistered",
+            ErrorReason::PayloadTooLarge => "PayloadTooLarge",
+            ErrorReason::TooManyProviderTokenUpdates => "TooManyProviderTokenUpdates",
+            ErrorReason::TooManyRequests => "TooManyRequests",
+            ErrorReason::InternalServerError => "InternalServerError",
+            ErrorReason::ServiceUnavailable => "ServiceUnavailable",
+            ErrorReason::Shutdown => "Shutdown",
+            ErrorReason::Unknown(reason) => reason,
+        }
+    }
+
+    /// The request can be sent again later without modifications. APNs is
+    /// either overloaded, shutting down or throttling the device token or the
+    /// provider token updates, or the provider token expired and the client
+    /// signs a new one for the next request.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ErrorReason::IdleTimeout
+                | ErrorReason::ExpiredProviderToken
+                | ErrorReason::TooManyProviderTokenUpdates
+                | ErrorReason::TooManyRequests
+                | ErrorReason::InternalServerError
+                | ErrorReason::ServiceUnavailable
+                | ErrorReason::Shutdown
+        )
+    }
+
+    /// The device token is not valid for the topic anymore and should not be
+    /// used for sending notifications until the device registers again.
+    pub fn should_remove_device_token(&self) -> bool {
+        matches!(
+            self,
+            ErrorReason::Unregistered
+                | ErrorReason::ExpiredToken
+                | ErrorReason::BadDeviceToken
+                | ErrorReason::DeviceTokenNotForTopic
+        )
+    }
+
+    /// The certificate or the provider token was not accepted. Every
+    /// notification sent with the same credentials will fail the same way.
+    pub fn is_auth_problem(&self) -> bool {
+        matches!(
+            self,
+            ErrorReason::BadCertificate
+                | ErrorReason::BadCertificateEnvironment
+                | ErrorReason::Forbidden
+                | ErrorReason::InvalidProviderToken
+                | ErrorReason::MissingProviderToken
+                | ErrorReason::UnrelatedKeyIdInToken
+                | ErrorReason::BadEnvironmentKeyInToken
+                | ErrorReason::FeatureNotEnabled
+        )
+    }
+
+    /// The payload or the `NotificationOptions` were not accepted. Sending
+    /// the same notification again will fail the same way.
+    pub fn is_payload_problem(&self) -> bool {
+        matches!(
+            self,
+            ErrorReason::BadCollapseId
+                | ErrorReason::BadExpirationDate
+                | ErrorReason::BadMessageId
+                | ErrorReason::BadPriority
+                | ErrorReason::BadTopic
+                | ErrorReason::InvalidPushType
+                | ErrorReason::BadChannelId
+                | ErrorReason::MissingChannelId
+                | ErrorReason::MissingPushType
+                | ErrorReason::DuplicateHeaders
+                | ErrorReason::MissingTopic
+                | ErrorReason::PayloadEmpty
+                | ErrorReason::PayloadTooLarge
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorReason {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let reason = String::deserialize(deserializer)?;
+
+        let reason = match reason.as_str() {
+            "BadCollapseId" => ErrorReason::BadCollapseId,
+            "BadDeviceToken" => ErrorReason::BadDeviceToken,
+            "BadExpirationDate" => ErrorReason::BadExpirationDate,
+            "BadMessageId" => ErrorReason::BadMessageId,
+            "BadPriority" => ErrorReason::BadPriority,
+            "BadTopic" => ErrorReason::BadTopic,
+            "InvalidPushType" => ErrorReason::InvalidPushType,
+            "BadChannelId" => ErrorReason::BadChannelId,
+            "ChannelNotRegistered" => ErrorReason::ChannelNotRegistered,
+            "MissingChannelId" => ErrorReason::MissingChannelId,
+            "CannotCreateChannelConfig" => ErrorReason::CannotCreateChannelConfig,
+            "FeatureNotEnabled" => ErrorReason::FeatureNotEnabled,
+            "MissingPushType" => ErrorReason::MissingPushType,
+            "DeviceTokenNotForTopic" => ErrorReason::DeviceTokenNotForTopic,
+            "DuplicateHeaders" => ErrorReason::DuplicateHeaders,
+            "IdleTimeout" => ErrorReason::IdleTimeout,
+            "MissingDeviceToken" => ErrorReason::MissingDeviceToken,
+            "MissingTopic" => ErrorReason::MissingTopic,
+            "PayloadEmpty" => ErrorReason::PayloadEmpty,
+            "TopicDisallowed" => ErrorReason::TopicDisallowed,
+            "BadCertificate" => ErrorReason::BadCertificate,
+            "BadCertificateEnvironment" => ErrorReason::BadCertificateEnvironment,
+            "ExpiredProviderToken" => ErrorReason::ExpiredProviderToken,
+            "Forbidden" => ErrorReason::Forbidden,
+            "InvalidProviderToken" => ErrorReason::InvalidProviderToken,
+            "MissingProviderToken" => ErrorReason::MissingProviderToken,
+            "UnrelatedKeyIdInToken" => ErrorReason::UnrelatedKeyIdInToken,
+            "BadEnvironmentKeyInToken" => ErrorReason::BadEnvironmentKeyInToken,
+            "BadPath" => ErrorReason::BadPath,
+            "MethodNotAllowed" => ErrorReason::MethodNotAllowed,
+            "ExpiredToken" => ErrorReason::ExpiredToken,
+            "Unregistered" => ErrorReason::Unregistered,
+            "PayloadTooLarge" => ErrorReason::PayloadTooLarge,
+            "TooManyProviderTokenUpdates" => ErrorReason::TooManyProviderTokenUpdates,
+            "TooManyRequests" => ErrorReason::TooManyRequests,
+            "InternalServerError" => ErrorReason::InternalServerError,
+            "ServiceUnavailable" => ErrorReason::ServiceUnavailable,
+            "Shutdown" => ErrorReason::Shutdown,
+            _ => ErrorReason::Unknown(reason),
+        };
+
+        Ok(reason)
+    }
 }
 
 impl fmt::Display for ErrorReason {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match *self {
+        let s = match self {
             ErrorReason::BadCollapseId =>
                 "The collapse identifier exceeds the maximum allowed size.",
             ErrorReason::BadDeviceToken =>
@@ -150,6 +378,20 @@ impl fmt::Display for ErrorReason {
                 "The `apns_priority` in `NotificationOptions` is bad.",
             ErrorReason::BadTopic =>
                 "The `apns_topic` in `NotificationOptions` is bad.",
+            ErrorReason::InvalidPushType =>
+                "The `apns-push-type` value is invalid.",
+            ErrorReason::BadChannelId =>
+                "The `apns-channel-id` of the broadcast channel is bad.",
+            ErrorReason::ChannelNotRegistered =>
+                "The broadcast channel does not exist.",
+            ErrorReason::MissingChannelId =>
+                "The `apns-channel-id` header was required but not specified.",
+            ErrorReason::CannotCreateChannelConfig =>
+                "The app has reached the maximum number of broadcast channels.",
+            ErrorReason::FeatureNotEnabled =>
+                "Broadcast push notifications are not enabled for the app.",
+            ErrorReason::MissingPushType =>
+                "The `apns-push-type` header was required but not specified.",
             ErrorReason::DeviceTokenNotForTopic =>
                 "The device token does not match the specified topic.",
             ErrorReason::DuplicateHeaders =>
@@ -176,10 +418,16 @@ impl fmt::Display for ErrorReason {
                 "The provider token is not valid or the token signature could not be verified.",
             ErrorReason::MissingProviderToken =>
                 "No provider certificate was used to connect to APNs and Authorization header was missing or no provider token was specified.",
+            ErrorReason::UnrelatedKeyIdInToken =>
+                "The key id in the provider token isn't related to the key id of the token used in the first push of this connection.",
+            ErrorReason::BadEnvironmentKeyInToken =>
+                "The environment of the key in the provider token doesn't match the environment of the endpoint.",
             ErrorReason::BadPath =>
                 "The request path value is bad.",
             ErrorReason::MethodNotAllowed =>
                 "The request method was not `POST`.",
+            ErrorReason::ExpiredToken =>
+                "The device token has expired.",
             ErrorReason::Unregistered =>
                 "The device token is inactive for the specified topic. You should stop sending notifications to this token.",
             ErrorReason::PayloadTooLarge =>
@@ -194,6 +442,8 @@ impl fmt::Display for ErrorReason {
                 "The service is unavailable.",
             ErrorReason::Shutdown =>
                 "The server is shutting down.",
+            ErrorReason::Unknown(reason) =>
+                reason.as_str(),
         };
 
         f.write_str(s)
@@ -214,6 +464,17 @@ mod tests {
             (ErrorReason::BadMessageId, "BadMessageId", None),
             (ErrorReason::BadPriority, "BadPriority", None),
             (ErrorReason::BadTopic, "BadTopic", None),
+            (ErrorReason::InvalidPushType, "InvalidPushType", None),
+            (ErrorReason::BadChannelId, "BadChannelId", None),
+            (ErrorReason::ChannelNotRegistered, "ChannelNotRegistered", None),
+            (ErrorReason::MissingChannelId, "MissingChannelId", None),
+            (
+                ErrorReason::CannotCreateChannelConfig,
+                "CannotCreateChannelConfig",
+                None,
+            ),
+            (ErrorReason::FeatureNotEnabled, "FeatureNotEnabled", None),
+            (ErrorReason::MissingPushType, "MissingPushType", None),
             (ErrorReason::DeviceTokenNotForTopic, "DeviceTokenNotForTopic", None),
             (ErrorReason::DuplicateHeaders, "DuplicateHeaders", None),
             (ErrorReason::IdleTimeout, "IdleTimeout", None),
@@ -231,8 +492,11 @@ mod tests {
             (ErrorReason::Forbidden, "Forbidden", None),
             (ErrorReason::InvalidProviderToken, "InvalidProviderToken", None),
             (ErrorReason::MissingProviderToken, "MissingProviderToken", None),
+            (ErrorReason::UnrelatedKeyIdInToken, "UnrelatedKeyIdInToken", None),
+            (ErrorReason::BadEnvironmentKeyInToken, "BadEnvironmentKeyInToken", None),
             (ErrorReason::BadPath, "BadPath", None),
             (ErrorReason::MethodNotAllowed, "MethodNotAllowed", None),
+            (ErrorReason::ExpiredToken, "ExpiredToken", None),
             (ErrorReason::Unregistered, "Unregistered", Some(1508249865488u64)),
             (ErrorReason::PayloadTooLarge, "PayloadTooLarge", None),
             (
@@ -263,11 +527,76 @@ mod tests {
                 },
                 Some(ts) => ErrorBody {
                     reason: error.0,
-                    timestamp: Some(ts),
+                    timestamp: Some(UNIX_EPOCH + Duration::from_millis(ts)),
                 },
             };
 
             assert_eq!(expected_body, response_body);
         }
     }
+
+    #[test]
+    fn test_unknown_error_response_parsing() {
+        let response_body: ErrorBody = serde_json::from_str(r#"{"reason":"SomethingNew"}"#).unwrap();
+
+        assert_eq!(
+            ErrorBody {
+                reason: ErrorReason::Unknown(String::from("SomethingNew")),
+                timestamp: None,
+            },
+            response_body
+        );
+
+        assert_eq!("SomethingNew", response_body.reason.as_str());
+        assert_eq!("SomethingNew", response_body.reason.to_string());
+    }
+
+    #[test]
+    fn test_error_reason_as_str() {
+        assert_eq!("Unregistered", ErrorReason::Unregistered.as_str());
+        assert_eq!("ExpiredToken", ErrorReason::ExpiredToken.as_str());
+    }
+
+    #[test]
+    fn test_error_reason_classification() {
+        assert!(ErrorReason::TooManyRequests.is_retryable());
+        assert!(ErrorReason::ServiceUnavailable.is_retryable());
+        assert!(!ErrorReason::BadDeviceToken.is_retryable());
+        assert!(!ErrorReason::CannotCreateChannelConfig.is_retryable());
+
+        // 403 ExpiredProviderToken: "The provider token is stale and a new
+        // token should be generated." The signer renews the token, so the
+        // same notification goes through with the next one.
+        assert!(ErrorReason::ExpiredProviderToken.is_retryable());
+        assert!(!ErrorReason::ExpiredProviderToken.is_auth_problem());
+
+        // 429 TooManyProviderTokenUpdates: "The provider's authentication
+        // token is being updated too often. Update the authentication token
+        // no more than once every 20 minutes." A rate limit to back off from,
+        // the credentials themselves are fine.
+        assert!(ErrorReason::TooManyProviderTokenUpdates.is_retryable());
+        assert!(!ErrorReason::TooManyProviderTokenUpdates.is_auth_problem());
+
+        assert!(ErrorReason::Unregistered.should_remove_device_token());
+        assert!(ErrorReason::BadDeviceToken.should_remove_device_token());
+        assert!(ErrorReason::DeviceTokenNotForTopic.should_remove_device_token());
+        assert!(ErrorReason::ExpiredToken.should_remove_device_token());
+        assert!(!ErrorReason::TooManyRequests.should_remove_device_token());
+
+        assert!(ErrorReason::InvalidProviderToken.is_auth_problem());
+        assert!(ErrorReason::BadCertificateEnvironment.is_auth_problem());
+        assert!(!ErrorReason::PayloadTooLarge.is_auth_problem());
+        assert!(ErrorReason::FeatureNotEnabled.is_auth_problem());
+
+        assert!(ErrorReason::PayloadTooLarge.is_payload_problem());
+        assert!(ErrorReason::BadCollapseId.is_payload_problem());
+        assert!(ErrorReason::MissingPushType.is_payload_problem());
+        assert!(!ErrorReason::Unregistered.is_payload_problem());
+
+        let unknown = ErrorReason::Unknown(String::from("SomethingNew"));
+        assert!(!unknown.is_retryable());
+        assert!(!unknown.should_remove_device_token());
+        assert!(!unknown.is_auth_problem());
+        assert!(!unknown.is_payload_problem());
+    }
 }
@@ -1,11 +1,13 @@
 use crate::error::Error;
+use crate::instrumentation;
 use base64::encode;
 use std::io::Read;
 use std::{
-    sync::RwLock,
+    sync::{PoisonError, RwLock},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+#[cfg(not(feature = "ring"))]
 use openssl::{
     ec::EcKey,
     hash::MessageDigest,
@@ -13,7 +15,18 @@ use openssl::{
     sign::Signer as SslSigner,
 };
 
-#[derive(Debug)]
+#[cfg(feature = "ring")]
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+
+#[cfg(not(feature = "ring"))]
+type Secret = PKey<Private>;
+
+#[cfg(feature = "ring")]
+type Secret = EcdsaKeyPair;
+
 struct Signature {
     key: String,
     issued_at: i64,
@@ -25,7 +38,7 @@ pub struct Signer {
     signature: RwLock<Signature>,
     key_id: String,
     team_id: String,
-    secret: PKey<Private>,
+    secret: Secret,
     expire_after_s: Duration,
 }
 
@@ -48,7 +61,8 @@ struct JwtPayload<'a> {
 
 impl Signer {
     /// Creates a signer with a pkcs8 private key, APNs key id and team id.
-    /// Can fail if the key is not valid or there is a problem with system OpenSSL.
+    /// Can fail if the key is not valid or, with the `openssl` feature, there
+    /// is a problem with system OpenSSL.
     pub fn new<S, T, R>(mut pk_pem: R, key_id: S, team_id: T, signature_ttl: Duration) -> Result<Signer, Error>
     where
         S: Into<String>,
@@ -61,10 +75,8 @@ impl Signer {
         let mut pem_key: Vec<u8> = Vec::new();
         pk_pem.read_to_end(&mut pem_key)?;
 
-        let ec_key = EcKey::private_key_from_pem(&pem_key)?;
-
+        let secret = Self::read_secret(&pem_key)?;
         let issued_at = get_time();
-        let secret = PKey::from_ec_key(ec_key)?;
 
         let signature = RwLock::new(Signature {
             key: Self::create_signature(&secret, &key_id, &team_id, issued_at)?,
@@ -83,16 +95,17 @@ impl Signer {
     }
 
     /// Take a signature out for usage. Automatically renews the signature
-    /// if it's older than the expiration time.
+    /// if it's older than the expiration time. Fails if renewing fails, or
+    /// if a renewal panicked earlier.
     pub fn with_signature<F, T>(&self, f: F) -> Result<T, Error>
     where
         F: FnOnce(&str) -> T,
     {
-        if self.is_expired() {
+        if self.is_expired()? {
             self.renew()?;
         }
 
-        let signature = self.signature.read().unwrap();
+        let signature = self.signature.read().map_err(poisoned)?;
 
         trace!(
             "Signer::with_signature found signature for {}/{} valid for {}s",
@@ -104,7 +117,42 @@ impl Signer {
         Ok(f(&signature.key))
     }
 
-    fn create_signature(secret: &PKey<Private>, key_id: &str, team_id: &str, issued_at: i64) -> Result<String, Error> {
+    #[cfg(not(feature = "ring"))]
+    fn read_secret(pem_key: &[u8]) -> Result<Secret, Error> {
+        let ec_key = EcKey::private_key_from_pem(pem_key)?;
+
+        Ok(PKey::from_ec_key(ec_key)?)
+    }
+
+    #[cfg(feature = "ring")]
+    fn read_secret(mut pem_key: &[u8]) -> Result<Secret, Error> {
+        let der = rustls_pemfile::pkcs8_private_keys(&mut pem_key)?
+            .pop()
+            .ok_or_else(|| Error::SignerError(String::from("No PKCS8 private key found")))?;
+
+        Ok(EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &der,
+            &SystemRandom::new(),
+        )?)
+    }
+
+    #[cfg(not(feature = "ring"))]
+    fn sign(secret: &Secret, signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut signer = SslSigner::new(MessageDigest::sha256(), secret)?;
+        signer.update(signing_input)?;
+
+        Ok(signer.sign_to_vec()?)
+    }
+
+    #[cfg(feature = "ring")]
+    fn sign(secret: &Secret, signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+        let signature = secret.sign(&SystemRandom::new(), signing_input)?;
+
+        Ok(signature.as_ref().to_vec())
+    }
+
+    fn create_signature(secret: &Secret, key_id: &str, team_id: &str, issued_at: i64) -> Result<String, Error> {
         let headers = JwtHeader {
             alg: JwtAlg::ES256,
             kid: key_id,
@@ -119,10 +167,7 @@ impl Signer {
         let encoded_payload = encode(&serde_json::to_string(&payload)?);
         let signing_input = format!("{}.{}", encoded_header, encoded_payload);
 
-        let mut signer = SslSigner::new(MessageDigest::sha256(), secret)?;
-        signer.update(signing_input.as_bytes())?;
-
-        let signature_payload = signer.sign_to_vec()?;
+        let signature_payload = Self::sign(secret, signing_input.as_bytes())?;
 
         Ok(format!("{}.{}", signing_input, encode(&signature_payload)))
     }
@@ -138,23 +183,39 @@ impl Signer {
             self.expire_after_s.as_secs(),
         );
 
-        let mut signature = self.signature.write().unwrap();
+        let mut signature = self.signature.write().map_err(poisoned)?;
 
         *signature = Signature {
             key: Self::create_signature(&self.secret, &self.key_id, &self.team_id, issued_at)?,
             issued_at,
         };
 
+        instrumentation::jwt_renewed();
+
         Ok(())
     }
 
-    fn is_expired(&self) -> bool {
-        let sig = self.signature.read().unwrap();
+    fn is_expired(&self) -> Result<bool, Error> {
+        let sig = self.signature.read().map_err(poisoned)?;
         let expiry = get_time() - sig.issued_at;
-        expiry >= self.expire_after_s.as_secs() as i64
+
+        Ok(expiry >= self.expire_after_s.as_secs() as i64)
+    }
+
+    /// Leaves the signature lock poisoned, for testing the failures.
+    #[cfg(test)]
+    pub(crate) fn poison(&self) {
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _signature = self.signature.write().unwrap();
+            panic!("poisoning the signature lock");
+        }));
     }
 }
 
+fn poisoned<T>(_: PoisonError<T>) -> Error {
+    Error::SignerError(String::from("The signature lock was poisoned by a panic"))
+}
+
 fn get_time() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -211,4 +272,19 @@ mod tests {
 
         assert_ne!(sig1, sig2);
     }
+
+    #[test]
+    fn test_signature_with_a_poisoned_lock() {
+        let signer = Signer::new(
+            PRIVATE_KEY.as_bytes(),
+            "89AFRD1X22",
+            "ASDFQWERTY",
+            Duration::from_secs(100),
+        )
+        .unwrap();
+
+        signer.poison();
+
+        assert!(matches!(signer.with_signature(|_| ()), Err(Error::SignerError(_))));
+    }
 }
@@ -0,0 +1,540 @@
+//! Server-side localization of notifications. A [Catalog](struct.Catalog.html)
+//! holds the notification texts of every locale, rendered in the locale of
+//! the recipient to fill a `PlainNotificationBuilder` or a
+//! `LocalizedNotificationBuilder`.
+//!
+//! The texts of a locale are given as JSON, keyed by the message name. A
+//! message has a `body` and optionally a `title` and a `subtitle`:
+//!
+//! ```json
+//! {
+//!     "new_messages": {
+//!         "title": "Hi {name}",
+//!         "body": {
+//!             "zero": "No new messages",
+//!             "one": "You have a new message",
+//!             "other": "You have {count} new messages"
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! A text is either a template with `{argument}` placeholders, or a plural
+//! with the `other` form and optional `zero`, `one`, `two`, `few` and `many`
+//! forms. The form is selected by the `count` argument with the [CLDR plural
+//! rules](https://cldr.unicode.org/index/cldr-spec/plural-rules) of the
+//! locale, using `other` for a missing form. As in the `stringsdict` files of
+//! Apple, a `zero` form is used for a count of zero also in the languages
+//! without a zero category. Literal braces are written as `{{` and `}}`.
+
+use crate::error::Error;
+use crate::request::notification::{LocalizedNotificationBuilder, PlainNotificationBuilder};
+use erased_serde::Serialize;
+use intl_pluralrules::operands::PluralOperands;
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use unic_langid::LanguageIdentifier;
+
+/// A text of a message, either a template or a plural selected by `count`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum Text {
+    Template(String),
+    Plural(Plural),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct Plural {
+    zero: Option<String>,
+    one: Option<String>,
+    two: Option<String>,
+    few: Option<String>,
+    many: Option<String>,
+    other: String,
+}
+
+impl Plural {
+    fn select(&self, locale: &str, count: PluralOperands) -> &str {
+        if count.n == 0.0 {
+            if let Some(ref zero) = self.zero {
+                return zero;
+            }
+        }
+
+        let form = match plural_rules(locale).map(|rules| rules.select(count)) {
+            Some(Ok(PluralCategory::ZERO)) => self.zero.as_ref(),
+            Some(Ok(PluralCategory::ONE)) => self.one.as_ref(),
+            Some(Ok(PluralCategory::TWO)) => self.two.as_ref(),
+            Some(Ok(PluralCategory::FEW)) => self.few.as_ref(),
+            Some(Ok(PluralCategory::MANY)) => self.many.as_ref(),
+            _ => None,
+        };
+
+        form.unwrap_or(&self.other)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct Message {
+    title: Option<Text>,
+    subtitle: Option<Text>,
+    body: Text,
+}
+
+/// The notification texts of all the locales.
+///
+/// The locale of the recipient is matched first as it is, then by the
+/// language only (`pt` for `pt-BR`), and last the fallback locale given when
+/// creating the catalog is used.
+///
+/// ```rust
+/// # use a2::template::Catalog;
+/// # use a2::NotificationBuilder;
+/// # use serde_json::json;
+/// # fn main() -> Result<(), a2::Error> {
+/// let mut catalog = Catalog::new("en");
+///
+/// catalog.add_locale("en", r#"{"greeting": {"title": "Hi {name}", "body": "Welcome back!"}}"#)?;
+/// catalog.add_locale("fi", r#"{"greeting": {"title": "Moi {name}", "body": "Tervetuloa takaisin!"}}"#)?;
+///
+/// let notification = catalog.render("fi-FI", "greeting", &json!({"name": "Aino"}))?;
+/// let payload = notification.localized_builder().build("token", Default::default());
+///
+/// assert_eq!(
+///     "{\"aps\":{\"alert\":{\"body\":\"Tervetuloa takaisin!\",\"title\":\"Moi Aino\"}}}",
+///     &payload.to_json_string()?
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    fallback_locale: String,
+    locales: HashMap<String, HashMap<String, Message>>,
+}
+
+impl Catalog {
+    /// An empty catalog, rendering in the `fallback_locale` when the locale
+    /// of the recipient is missing.
+    pub fn new(fallback_locale: &str) -> Catalog {
+        Catalog {
+            fallback_locale: normalize_locale(fallback_locale),
+            locales: HashMap::new(),
+        }
+    }
+
+    /// Adds the messages of a locale from JSON, replacing the earlier
+    /// messages of the locale.
+    pub fn add_locale(&mut self, locale: &str, messages_json: &str) -> Result<&mut Self, Error> {
+        let messages = serde_json::from_str(messages_json)?;
+        self.locales.insert(normalize_locale(locale), messages);
+
+        Ok(self)
+    }
+
+    /// Renders the message `key` in the locale of the recipient. The
+    /// `arguments` are any value serializing into a JSON object, such as a
+    /// struct with `#[derive(Serialize)]` or the `json!` macro.
+    pub fn render(&self, locale: &str, key: &str, arguments: &dyn Serialize) -> Result<RenderedNotification, Error> {
+        let (locale, message) = self
+            .candidate_locales(locale)
+            .into_iter()
+            .find_map(|candidate| {
+                let message = self.locales.get(&candidate)?.get(key)?;
+                Some((candidate, message))
+            })
+            .ok_or_else(|| Error::TemplateError(format!("No message {} for the locale {}", key, locale)))?;
+
+        let arguments = match serde_json::to_value(arguments)? {
+            Value::Object(arguments) => arguments,
+            Value::Null => Map::new(),
+            _ => {
+                return Err(Error::TemplateError(String::from(
+                    "The template arguments must be a JSON object",
+                )))
+            }
+        };
+
+        Ok(RenderedNotification {
+            title: message
+                .title
+                .as_ref()
+                .map(|title| render(title, &locale, &arguments))
+                .transpose()?,
+            subtitle: message
+                .subtitle
+                .as_ref()
+                .map(|subtitle| render(subtitle, &locale, &arguments))
+                .transpose()?,
+            body: render(&message.body, &locale, &arguments)?,
+            locale,
+        })
+    }
+
+    fn candidate_locales(&self, locale: &str) -> Vec<String> {
+        let locale = normalize_locale(locale);
+        let mut candidates = vec![locale.clone()];
+
+        if let Some((language, _)) = locale.split_once('-') {
+            candidates.push(String::from(language));
+        }
+
+        candidates.push(self.fallback_locale.clone());
+        candidates
+    }
+}
+
+/// The texts of a message rendered in the locale of the recipient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedNotification {
+    /// The catalog locale the message was rendered in.
+    pub locale: String,
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub body: String,
+}
+
+impl RenderedNotification {
+    /// A builder with the body of the message. The title and the subtitle
+    /// are left out, see [localized_builder](#method.localized_builder).
+    pub fn plain_builder(&self) -> PlainNotificationBuilder<'_> {
+        PlainNotificationBuilder::new(&self.body)
+    }
+
+    /// A builder with the title, the subtitle and the body of the message. A
+    /// message without a title is sent without one.
+    pub fn localized_builder(&self) -> LocalizedNotificationBuilder<'_> {
+        let mut builder = match self.title {
+            Some(ref title) => LocalizedNotificationBuilder::new(title, &self.body),
+            None => LocalizedNotificationBuilder::without_title(&self.body),
+        };
+
+        if let Some(ref subtitle) = self.subtitle {
+            builder.set_subtitle(subtitle);
+        }
+
+        builder
+    }
+}
+
+/// Locales are matched case-insensitively, with either `-` or `_` between
+/// the language and the region.
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_lowercase()
+}
+
+/// The cardinal plural rules of the locale, or of its language for a region
+/// without rules of its own.
+fn plural_rules(locale: &str) -> Option<PluralRules> {
+    let language = locale.split('-').next().unwrap_or(locale);
+
+    [locale, language].iter().find_map(|candidate| {
+        let langid: LanguageIdentifier = candidate.parse().ok()?;
+        PluralRules::create(langid, PluralRuleType::CARDINAL).ok()
+    })
+}
+
+fn render(text: &Text, locale: &str, arguments: &Map<String, Value>) -> Result<String, Error> {
+    let template = match text {
+        Text::Template(template) => template,
+        Text::Plural(plural) => {
+            let count = match arguments.get("count") {
+                Some(Value::Number(count)) => PluralOperands::try_from(count.to_string().as_str()).ok(),
+                _ => None,
+            }
+            .ok_or_else(|| Error::TemplateError(String::from("A plural needs a numeric count argument")))?;
+
+            plural.select(locale, count)
+        }
+    };
+
+    substitute(template, arguments)
+}
+
+fn substitute(template: &str, arguments: &Map<String, Value>) -> Result<String, Error> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                rendered.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                rendered.push('}');
+            }
+            '{' => {
+                let rest = chars.as_str();
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| Error::TemplateError(format!("Unclosed placeholder in {}", template)))?;
+
+                let name = rest[..end].trim();
+
+                match arguments.get(name) {
+                    Some(Value::String(value)) => rendered.push_str(value),
+                    Some(value) => rendered.push_str(&value.to_string()),
+                    None => return Err(Error::TemplateError(format!("Missing argument {}", name))),
+                }
+
+                chars = rest[end + 1..].chars();
+            }
+            '}' => return Err(Error::TemplateError(format!("Unopened placeholder in {}", template))),
+            c => rendered.push(c),
+        }
+    }
+
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::notification::NotificationBuilder;
+
+    fn catalog() -> Catalog {
+        let mut catalog = Catalog::new("en");
+
+        catalog
+            .add_locale(
+                "en",
+                &json!({
+                    "new_messages": {
+                        "title": "Hi {name}",
+                        "subtitle": "Inbox",
+                        "body": {
+                            "zero": "No new messages",
+                            "one": "You have a new message",
+                            "other": "You have {count} new messages"
+                        }
+                    },
+                    "braces": {"body": "{{literally}} {name}"}
+                })
+                .to_string(),
+            )
+            .unwrap();
+
+        catalog
+            .add_locale(
+                "pt_BR",
+                &json!({
+                    "new_messages": {
+                        "title": "Olá {name}",
+                        "body": {"one": "Você tem {count} mensagem nova", "other": "Você tem {count} mensagens novas"}
+                    }
+                })
+                .to_string(),
+            )
+            .unwrap();
+
+        catalog
+    }
+
+    #[test]
+    fn test_render_plurals() {
+        let catalog = catalog();
+
+        let zero = catalog
+            .render("en", "new_messages", &json!({"name": "Jane", "count": 0}))
+            .unwrap();
+        let one = catalog
+            .render("en", "new_messages", &json!({"name": "Jane", "count": 1}))
+            .unwrap();
+        let other = catalog
+            .render("en", "new_messages", &json!({"name": "Jane", "count": 3}))
+            .unwrap();
+
+        assert_eq!("No new messages", zero.body);
+        assert_eq!("You have a new message", one.body);
+        assert_eq!("You have 3 new messages", other.body);
+        assert_eq!(Some("Hi Jane"), other.title.as_deref());
+        assert_eq!(Some("Inbox"), other.subtitle.as_deref());
+    }
+
+    #[test]
+    fn test_render_without_a_zero_form() {
+        let notification = catalog()
+            .render("pt-BR", "new_messages", &json!({"name": "Ana", "count": 0}))
+            .unwrap();
+
+        // Portuguese has zero in the `one` category.
+        assert_eq!("pt-br", notification.locale);
+        assert_eq!("Você tem 0 mensagem nova", notification.body);
+    }
+
+    #[test]
+    fn test_render_plural_categories_of_the_locale() {
+        let mut catalog = Catalog::new("en");
+
+        catalog
+            .add_locale(
+                "pl",
+                &json!({
+                    "files": {
+                        "body": {
+                            "one": "{count} plik",
+                            "few": "{count} pliki",
+                            "many": "{count} plików",
+                            "other": "{count} pliku"
+                        }
+                    }
+                })
+                .to_string(),
+            )
+            .unwrap();
+
+        catalog
+            .add_locale(
+                "ar",
+                &json!({
+                    "files": {
+                        "body": {
+                            "zero": "zero",
+                            "one": "one",
+                            "two": "two",
+                            "few": "few",
+                            "many": "many",
+                            "other": "other"
+                        }
+                    }
+                })
+                .to_string(),
+            )
+            .unwrap();
+
+        let render = |locale, count: Value| {
+            catalog
+                .render(locale, "files", &json!({ "count": count }))
+                .unwrap()
+                .body
+        };
+
+        assert_eq!("1 plik", render("pl-PL", json!(1)));
+        assert_eq!("3 pliki", render("pl-PL", json!(3)));
+        assert_eq!("22 pliki", render("pl-PL", json!(22)));
+        assert_eq!("5 plików", render("pl-PL", json!(5)));
+        assert_eq!("12 plików", render("pl-PL", json!(12)));
+        assert_eq!("1.5 pliku", render("pl-PL", json!(1.5)));
+        assert_eq!("1.0 pliku", render("pl-PL", json!(1.0)));
+
+        assert_eq!("zero", render("ar", json!(0)));
+        assert_eq!("one", render("ar", json!(1)));
+        assert_eq!("two", render("ar", json!(2)));
+        assert_eq!("few", render("ar", json!(3)));
+        assert_eq!("many", render("ar", json!(11)));
+        assert_eq!("other", render("ar", json!(100)));
+    }
+
+    #[test]
+    fn test_locale_fallbacks() {
+        let catalog = catalog();
+        let arguments = json!({"name": "Ana", "count": 2});
+
+        assert_eq!(
+            "pt-br",
+            catalog.render("PT_br", "new_messages", &arguments).unwrap().locale
+        );
+        assert_eq!(
+            "en",
+            catalog.render("en-GB", "new_messages", &arguments).unwrap().locale
+        );
+        assert_eq!(
+            "en",
+            catalog.render("de-DE", "new_messages", &arguments).unwrap().locale
+        );
+        assert_eq!("en", catalog.render("pt-BR", "braces", &arguments).unwrap().locale);
+    }
+
+    #[test]
+    fn test_render_braces() {
+        let notification = catalog().render("en", "braces", &json!({"name": "Jane"})).unwrap();
+
+        assert_eq!("{literally} Jane", notification.body);
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let catalog = catalog();
+
+        assert!(matches!(
+            catalog.render("en", "unknown", &json!({})),
+            Err(Error::TemplateError(_))
+        ));
+        assert!(matches!(
+            catalog.render("en", "new_messages", &json!({"count": 2})),
+            Err(Error::TemplateError(_))
+        ));
+        assert!(matches!(
+            catalog.render("en", "new_messages", &json!({"name": "Jane"})),
+            Err(Error::TemplateError(_))
+        ));
+        assert!(matches!(
+            Catalog::new("en").add_locale("en", r#"{"broken": {"title": "no body"}}"#),
+            Err(Error::SerializeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_rendered_builders() {
+        let notification = catalog()
+            .render("en", "new_messages", &json!({"name": "Jane", "count": 1}))
+            .unwrap();
+
+        let plain = notification
+            .plain_builder()
+            .build("device-token", Default::default())
+            .to_json_string()
+            .unwrap();
+
+        let localized = notification
+            .localized_builder()
+            .build("device-token", Default::default())
+            .to_json_string()
+            .unwrap();
+
+        assert_eq!(json!({"aps": {"alert": "You have a new message"}}).to_string(), plain);
+
+        assert_eq!(
+            json!({
+                "aps": {
+                    "alert": {
+                        "title": "Hi Jane",
+                        "subtitle": "Inbox",
+                        "body": "You have a new message"
+                    }
+                }
+            })
+            .to_string(),
+            localized
+        );
+    }
+
+    #[test]
+    fn test_rendered_builder_without_a_title() {
+        let localized = catalog()
+            .render("en", "braces", &json!({"name": "Jane"}))
+            .unwrap()
+            .localized_builder()
+            .build("device-token", Default::default())
+            .to_json_string()
+            .unwrap();
+
+        assert_eq!(
+            json!({
+                "aps": {
+                    "alert": {
+                        "body": "{literally} Jane"
+                    }
+                }
+            })
+            .to_string(),
+            localized
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! The TLS connectors for the OpenSSL and rustls backends.
+//!
+//! When both `openssl` and `ring` features are enabled, rustls and ring are
+//! used.
+
+use crate::error::Error;
+
+#[cfg(not(feature = "ring"))]
+pub use self::openssl_backend::{certificate_connector, default_connector, Connector};
+
+#[cfg(feature = "ring")]
+pub use self::rustls_backend::{certificate_connector, default_connector, Connector};
+
+#[cfg(not(feature = "ring"))]
+mod openssl_backend {
+    use super::Error;
+    use hyper_alpn::AlpnConnector;
+    use openssl::pkcs12::Pkcs12;
+
+    /// HTTP/2 over TLS with ALPN, using the system OpenSSL.
+    pub type Connector = AlpnConnector;
+
+    /// A connector verifying the server with the system certificates.
+    pub fn default_connector() -> Connector {
+        AlpnConnector::new()
+    }
+
+    /// A connector authenticating with the certificate and private key from
+    /// a PKCS12 database.
+    pub fn certificate_connector(pkcs12_der: &[u8], password: &str) -> Result<Connector, Error> {
+        let pkcs = Pkcs12::from_der(pkcs12_der)?.parse2(password)?;
+
+        let (cert, pkey) = match (pkcs.cert, pkcs.pkey) {
+            (Some(cert), Some(pkey)) => (cert, pkey),
+            _ => {
+                return Err(Error::SignerError(String::from(
+                    "No certificate and private key in the PKCS12 database",
+                )))
+            }
+        };
+
+        AlpnConnector::with_client_cert(&cert.to_pem()?, &pkey.private_key_to_pem_pkcs8()?)
+            .map_err(|e| Error::TlsError(e.to_string()))
+    }
+}
+
+#[cfg(feature = "ring")]
+mod rustls_backend {
+    use super::Error;
+    use hyper::client::HttpConnector;
+    use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+    use p12_keystore::KeyStore;
+    use rustls::client::WantsTransparencyPolicyOrClientCert;
+    use rustls::{Certificate, ClientConfig, ConfigBuilder, OwnedTrustAnchor, PrivateKey, RootCertStore};
+
+    /// HTTP/2 over TLS with ALPN, using rustls.
+    pub type Connector = HttpsConnector<HttpConnector>;
+
+    /// A connector verifying the server with the bundled Mozilla root
+    /// certificates.
+    pub fn default_connector() -> Connector {
+        build_connector(config_builder().with_no_client_auth())
+    }
+
+    /// A connector authenticating with the certificate chain and private key
+    /// from a PKCS12 database.
+    pub fn certificate_connector(pkcs12_der: &[u8], password: &str) -> Result<Connector, Error> {
+        let keystore = KeyStore::from_pkcs12(pkcs12_der, password)?;
+
+        let (_, key_chain) = keystore
+            .private_key_chain()
+            .ok_or_else(|| Error::SignerError(String::from("No private key in the PKCS12 database")))?;
+
+        let certificates = key_chain
+            .chain()
+            .iter()
+            .map(|cert| Certificate(cert.as_der().to_vec()))
+            .collect();
+
+        let private_key = PrivateKey(key_chain.key().to_vec());
+        let config = config_builder().with_client_auth_cert(certificates, private_key)?;
+
+        Ok(build_connector(config))
+    }
+
+    fn config_builder() -> ConfigBuilder<ClientConfig, WantsTransparencyPolicyOrClientCert> {
+        let mut roots = RootCertStore::empty();
+
+        roots.add_trust_anchors(
+            webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+                OwnedTrustAnchor::from_subject_spki_name_constraints(ta.subject, ta.spki, ta.name_constraints)
+            }),
+        );
+
+        ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+    }
+
+    fn build_connector(config: ClientConfig) -> Connector {
+        HttpsConnectorBuilder::new()
+            .with_tls_config(config)
+            .https_only()
+            .enable_http2()
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A self-signed certificate and a P-256 key, password `a2`.
+    const PKCS12: &str = indoc!(
+        "MIIETAIBAzCCBAIGCSqGSIb3DQEHAaCCA/MEggPvMIID6zCCApoGCSqGSIb3DQEHBqCCAoswggKH
+        AgEAMIICgAYJKoZIhvcNAQcBMF8GCSqGSIb3DQEFDTBSMDEGCSqGSIb3DQEFDDAkBBB2kSJBqlLt
+        cD0iqnEIPAsaAgIIADAMBggqhkiG9w0CCQUAMB0GCWCGSAFlAwQBKgQQq7jEi4dQmUYPbTuoXF8G
+        6YCCAhCFrLrt/q3XfraEbDkH41o/lmrqgVw8O+kUS6hWy8B0Ahy4Y5XmYxDYtf8CHWXcDQKadSNg
+        UN0FEvp6lkebrIvhwd1Q1BX0gcnplcoA04Hmmrr88hHddAU9ul2TZpwCuLciXG1IbYy4Pe6moyy5
+        eGdGKKpdaGVzHu4HUCRJYSizt/nLoiKBNRs5xlB/2VH4f5OYfegIkZiyvGDdzrtEAcmf0k2xYKx5
+        B8dRr+pNydONXrn9KDXgIlmdlv6uWc67hAebXOJOxDMsFxhvYSWl8rJafkqi6MUoZRcT6t+WteK7
+        lZRuVX/WYYtoC3hwId87iJHI/8KUkL7XCwTbONySUecAVKGHQydGL8Yx/G5L12BS+6otiss90TJr
+        H9vSEE4Sc5KIWdUbohQHBf2TyLEXmAbHpdKW/rjNiDohXX4hZg8xlXXXPBc34VpJY8lbK8M6cf6o
+        FAkw4NAdAHwEKJb7VySTUaxLI3Fq9dvB3150R0wprUGcd/yvv1np4mKn0ZZOSWeu8vieajhJ/a3j
+        k3O+Izpj8mv+FBxrGaKv1XhYtvs2OquFXSQWvS+qRDqVJLOEkOU6G0C4sUdxRozK1sVO2G/uSoqN
+        ukCd6f6wkxkv6iqsCxwaNe01ylWL0qDvljkhCe14v2BDTxHQO/ZV6+OtStssG/doz5vFjY24VTNi
+        fNOvQepIbLh0RLHDosHopZclpdgwggFJBgkqhkiG9w0BBwGgggE6BIIBNjCCATIwggEuBgsqhkiG
+        9w0BDAoBAqCB9zCB9DBfBgkqhkiG9w0BBQ0wUjAxBgkqhkiG9w0BBQwwJAQQ2hNvWgAjV9vo51ID
+        +0clFAICCAAwDAYIKoZIhvcNAgkFADAdBglghkgBZQMEASoEEC+0I6puuk03FswPfa6AcbEEgZDu
+        h6zQNp6R7kqCaY4LpYT+RwcekTJ+l8rgzzkYLj6GPZ+QNe03xS5QD6+mO0eXkoEOxmEWN2PjvJV9
+        8bVxxcjLIFHAH15HjFgHxK3e0iTM+gUy12/4C3sVvL8cMQDvyvhJzecGpTjU13xmd/DmdMty4eJr
+        iLgHHq8VCi32jLaMs+lWMmOc+8+fk0+rZrrnFJIxJTAjBgkqhkiG9w0BCRUxFgQU2MWljwOKTIXp
+        UHcxNBWZ4LKXW+YwQTAxMA0GCWCGSAFlAwQCAQUABCCvO+TR+kX/FFFMuMwFTpHhef5rStbyyG31
+        Rf4wGqqJeAQIrib66bydQ2sCAggA"
+    );
+
+    fn pkcs12_der() -> Vec<u8> {
+        base64::decode(PKCS12.replace('\n', "")).unwrap()
+    }
+
+    #[test]
+    fn test_certificate_connector() {
+        assert!(certificate_connector(&pkcs12_der(), "a2").is_ok());
+    }
+
+    #[test]
+    fn test_certificate_connector_with_wrong_password() {
+        assert!(certificate_connector(&pkcs12_der(), "wrong").is_err());
+    }
+
+    #[test]
+    fn test_certificate_connector_with_garbage() {
+        assert!(certificate_connector(b"not a pkcs12 database", "a2").is_err());
+    }
+}